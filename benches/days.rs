@@ -0,0 +1,98 @@
+//! One Criterion group per day, benchmarking parse, part 1 and part 2 on the
+//! input in `data/` (if present), and on generated inputs of several sizes for
+//! the days that have a generator. Run with `cargo bench`, or e.g.
+//! `cargo bench -- day12` to run a single day.
+use aoc2022::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::fs;
+use std::hint::black_box;
+
+mod generators;
+
+/// Benchmarks each step of the given day on the given input
+macro_rules! bench_steps {
+    ($group:ident, $module:ident, $input:expr, $parameter:expr, [$($part:ident),+]) => {
+        let input = $input;
+        let data = $module::parse(&input);
+        $group.bench_with_input(BenchmarkId::new("parse", $parameter), &input, |b, input| {
+            b.iter(|| $module::parse(black_box(input)))
+        });
+        $(
+            $group.bench_with_input(BenchmarkId::new(stringify!($part), $parameter), &data, |b, data| {
+                b.iter(|| $module::$part(black_box(data)))
+            });
+        )+
+    };
+}
+
+macro_rules! bench_day {
+    ($name:ident, $module:ident, $day:literal, sizes: [$($size:literal),*], sample_size: $sample_size:literal, parts: $parts:tt) => {
+        fn $name(c: &mut Criterion) {
+            let mut group = c.benchmark_group(stringify!($module));
+            group.sample_size($sample_size);
+            if let Ok(input) = fs::read_to_string(format!("data/day{:02}.txt", $day)) {
+                bench_steps!(group, $module, input.trim_end().to_string(), "data", $parts);
+            }
+            $(
+                bench_steps!(group, $module, generators::$module($size), $size, $parts);
+            )*
+            group.finish();
+        }
+    };
+}
+
+bench_day!(bench_day01, day01, 1, sizes: [100, 1000, 10000], sample_size: 100, parts: [part1, part2]);
+bench_day!(bench_day02, day02, 2, sizes: [1000, 10000, 100000], sample_size: 100, parts: [part1, part2]);
+bench_day!(bench_day03, day03, 3, sizes: [300, 3000, 30000], sample_size: 100, parts: [part1, part2]);
+bench_day!(bench_day04, day04, 4, sizes: [1000, 10000, 100000], sample_size: 100, parts: [part1, part2]);
+bench_day!(bench_day05, day05, 5, sizes: [1000, 10000, 100000], sample_size: 100, parts: [part1, part2]);
+bench_day!(bench_day06, day06, 6, sizes: [1000, 10000, 100000], sample_size: 100, parts: [part1, part2]);
+bench_day!(bench_day07, day07, 7, sizes: [100, 1000, 10000], sample_size: 100, parts: [part1, part2]);
+bench_day!(bench_day08, day08, 8, sizes: [50, 100, 200], sample_size: 20, parts: [part1, part2]);
+bench_day!(bench_day09, day09, 9, sizes: [1000, 10000, 100000], sample_size: 20, parts: [part1, part2]);
+bench_day!(bench_day10, day10, 10, sizes: [], sample_size: 100, parts: [part1, part2]);
+bench_day!(bench_day11, day11, 11, sizes: [20, 100, 500], sample_size: 20, parts: [part1, part2]);
+bench_day!(bench_day12, day12, 12, sizes: [100, 200, 400], sample_size: 20, parts: [part1, part2]);
+bench_day!(bench_day13, day13, 13, sizes: [100, 1000, 10000], sample_size: 20, parts: [part1, part2]);
+bench_day!(bench_day14, day14, 14, sizes: [50, 100, 200], sample_size: 10, parts: [part1, part2]);
+bench_day!(bench_day15, day15, 15, sizes: [30, 300, 3000], sample_size: 100, parts: [part1, part2]);
+bench_day!(bench_day16, day16, 16, sizes: [6, 10, 15], sample_size: 10, parts: [part1, part2]);
+bench_day!(bench_day17, day17, 17, sizes: [1000, 10000, 40000], sample_size: 20, parts: [part1, part2]);
+bench_day!(bench_day18, day18, 18, sizes: [500, 1000, 2000], sample_size: 10, parts: [part1, part2]);
+bench_day!(bench_day19, day19, 19, sizes: [3, 10, 30], sample_size: 10, parts: [part1, part2]);
+bench_day!(bench_day20, day20, 20, sizes: [700, 1500, 5000], sample_size: 10, parts: [part1, part2]);
+bench_day!(bench_day21, day21, 21, sizes: [1_000, 100_000], sample_size: 100, parts: [part1, part2]);
+bench_day!(bench_day22, day22, 22, sizes: [10, 50, 200], sample_size: 100, parts: [part1, part2]);
+bench_day!(bench_day23, day23, 23, sizes: [10, 20, 40], sample_size: 10, parts: [part1, part2]);
+bench_day!(bench_day24, day24, 24, sizes: [25, 50, 100], sample_size: 10, parts: [part1, part2]);
+bench_day!(bench_day25, day25, 25, sizes: [100, 1000, 10000], sample_size: 100, parts: [part1]);
+
+criterion_group!(
+    benches,
+    bench_day01,
+    bench_day02,
+    bench_day03,
+    bench_day04,
+    bench_day05,
+    bench_day06,
+    bench_day07,
+    bench_day08,
+    bench_day09,
+    bench_day10,
+    bench_day11,
+    bench_day12,
+    bench_day13,
+    bench_day14,
+    bench_day15,
+    bench_day16,
+    bench_day17,
+    bench_day18,
+    bench_day19,
+    bench_day20,
+    bench_day21,
+    bench_day22,
+    bench_day23,
+    bench_day24,
+    bench_day25
+);
+criterion_main!(benches);
@@ -0,0 +1,570 @@
+//! Generators of random puzzle inputs of various sizes, used to check how the
+//! solutions scale. Day 10 has none, since only its first 240 cycles matter
+//! whatever the length of the program.
+use itertools::Itertools;
+
+/// A tiny xorshift pseudo-random number generator, so the generated inputs are
+/// reproducible and we don't need an extra dependency
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a random number in the range low..high
+    pub fn range(&mut self, low: i64, high: i64) -> i64 {
+        low + (self.next() % (high - low) as u64) as i64
+    }
+
+    /// Shuffles the items in place (Fisher-Yates)
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            items.swap(index, self.range(0, index as i64 + 1) as usize);
+        }
+    }
+}
+
+/// `size` elves carrying 1 to 10 snacks each
+pub fn day01(size: usize) -> String {
+    let mut rng = Rng::new(1);
+    (0..size)
+        .map(|_| {
+            (0..rng.range(1, 11))
+                .map(|_| rng.range(1000, 60000))
+                .join("\n")
+        })
+        .join("\n\n")
+}
+
+/// `size` rounds of rock paper scissors
+pub fn day02(size: usize) -> String {
+    let mut rng = Rng::new(2);
+    (0..size)
+        .map(|_| {
+            let elf = (b'A' + rng.range(0, 3) as u8) as char;
+            let me = (b'X' + rng.range(0, 3) as u8) as char;
+            format!("{elf} {me}")
+        })
+        .join("\n")
+}
+
+/// `size` rucksacks (rounded down to a multiple of 3). In each group of three,
+/// the letters are split between the rucksacks and their compartments so that
+/// each rucksack has a single item in both compartments, and the group a
+/// single badge.
+pub fn day03(size: usize) -> String {
+    let mut rng = Rng::new(3);
+    let mut rucksacks = vec![];
+    for _ in 0..size / 3 {
+        let mut letters = (b'a'..=b'z').chain(b'A'..=b'Z').collect_vec();
+        rng.shuffle(&mut letters);
+        let badge = letters[0];
+        for pool in letters[1..].chunks(17) {
+            let (misplaced, left_pool, right_pool) = (pool[0], &pool[1..9], &pool[9..]);
+            let length = rng.range(8, 17) as usize;
+            let mut left = vec![misplaced, badge];
+            let mut right = vec![misplaced];
+            left.extend((2..length).map(|_| left_pool[rng.range(0, 8) as usize]));
+            right.extend((1..length).map(|_| right_pool[rng.range(0, 8) as usize]));
+            rng.shuffle(&mut left);
+            rng.shuffle(&mut right);
+            left.extend(right);
+            rucksacks.push(String::from_utf8(left).unwrap());
+        }
+    }
+    rucksacks.join("\n")
+}
+
+/// `size` pairs of section assignments
+pub fn day04(size: usize) -> String {
+    let mut rng = Rng::new(4);
+    (0..size)
+        .map(|_| {
+            let (a, b) = (rng.range(1, 100), rng.range(1, 100));
+            let (c, d) = (rng.range(1, 100), rng.range(1, 100));
+            format!("{}-{},{}-{}", a.min(b), a.max(b), c.min(d), c.max(d))
+        })
+        .join("\n")
+}
+
+/// 9 stacks of 8 crates, and `size` moves that never empty a stack
+pub fn day05(size: usize) -> String {
+    let mut rng = Rng::new(5);
+    let mut heights = [8; 9];
+    let mut moves = vec![];
+    for _ in 0..size {
+        let from = loop {
+            let from = rng.range(0, 9) as usize;
+            if heights[from] > 1 {
+                break from;
+            }
+        };
+        let to = (from + rng.range(1, 9) as usize) % 9;
+        let quantity = rng.range(1, heights[from].min(10));
+        heights[from] -= quantity;
+        heights[to] += quantity;
+        moves.push(format!("move {quantity} from {} to {}", from + 1, to + 1));
+    }
+    let stacks = (0..8)
+        .map(|_| {
+            (0..9)
+                .map(|_| format!("[{}]", (b'A' + rng.range(0, 26) as u8) as char))
+                .join(" ")
+        })
+        .chain([(1..=9).map(|index| format!(" {index} ")).join(" ")])
+        .join("\n");
+    format!("{stacks}\n\n{}", moves.join("\n"))
+}
+
+/// A datastream of `size` characters, which only contains 14 different
+/// characters in a row at the very end
+pub fn day06(size: usize) -> String {
+    let mut rng = Rng::new(6);
+    let noise = (0..size.saturating_sub(14))
+        .map(|_| (b'a' + rng.range(0, 8) as u8) as char)
+        .collect::<String>();
+    noise + "abcdefghijklmn"
+}
+
+fn list_directory(
+    children: &[Vec<usize>],
+    rng: &mut Rng,
+    directory: usize,
+    lines: &mut Vec<String>,
+) {
+    // about 45M in total, so that some directory has to be deleted in part 2
+    let max_file_size = (45_000_000 / children.len() as i64).max(2000);
+    lines.push("$ ls".to_string());
+    for &child in &children[directory] {
+        lines.push(format!("dir d{child}"));
+    }
+    for index in 0..rng.range(0, 5) {
+        lines.push(format!("{} f{index}.txt", rng.range(1000, max_file_size)));
+    }
+    for &child in &children[directory] {
+        lines.push(format!("$ cd d{child}"));
+        list_directory(children, rng, child, lines);
+        lines.push("$ cd ..".to_string());
+    }
+}
+
+/// The terminal output of a depth-first listing of a random tree of `size`
+/// directories
+pub fn day07(size: usize) -> String {
+    let mut rng = Rng::new(7);
+    let mut children = vec![vec![]; size.max(1)];
+    for directory in 1..size {
+        children[rng.range(0, directory as i64) as usize].push(directory);
+    }
+    let mut lines = vec!["$ cd /".to_string()];
+    list_directory(&children, &mut rng, 0, &mut lines);
+    lines.join("\n")
+}
+
+/// A `size` × `size` forest
+pub fn day08(size: usize) -> String {
+    let mut rng = Rng::new(8);
+    (0..size)
+        .map(|_| (0..size).map(|_| rng.range(0, 10).to_string()).join(""))
+        .join("\n")
+}
+
+/// `size` rope moves
+pub fn day09(size: usize) -> String {
+    let mut rng = Rng::new(9);
+    (0..size)
+        .map(|_| {
+            format!(
+                "{} {}",
+                ["U", "D", "L", "R"][rng.range(0, 4) as usize],
+                rng.range(1, 20)
+            )
+        })
+        .join("\n")
+}
+
+/// 8 monkeys holding `size` items between them. The worry levels are reduced
+/// modulo the product of the divisors, so there can't be many more monkeys
+/// before `old * old` overflows; the cost of part 2 grows with the items.
+pub fn day11(size: usize) -> String {
+    let mut rng = Rng::new(11);
+    let mut divisors = [2, 3, 5, 7, 11, 13, 17, 19];
+    rng.shuffle(&mut divisors);
+    let mut items = vec![vec![]; 8];
+    for index in 0..size.max(8) {
+        let monkey = if index < 8 {
+            index
+        } else {
+            rng.range(0, 8) as usize
+        };
+        items[monkey].push(rng.range(50, 100));
+    }
+    (0..8)
+        .map(|index| {
+            let operation = match index {
+                0 => "old * old".to_string(),
+                _ if index % 2 == 0 => format!("old * {}", rng.range(2, 20)),
+                _ => format!("old + {}", rng.range(1, 9)),
+            };
+            let if_true = (index + rng.range(1, 8) as usize) % 8;
+            let if_false = (index + rng.range(1, 8) as usize) % 8;
+            format!(
+                "Monkey {index}:\n  Starting items: {}\n  Operation: new = {operation}\n  \
+                 Test: divisible by {}\n    If true: throw to monkey {if_true}\n    \
+                 If false: throw to monkey {if_false}",
+                items[index].iter().join(", "),
+                divisors[index]
+            )
+        })
+        .join("\n\n")
+}
+
+/// A heightmap of `size` × `size` / 4 cells rising from west to east, with some
+/// random dips, except in the middle row, so that there is always a path from
+/// S to E
+pub fn day12(size: usize) -> String {
+    let mut rng = Rng::new(12);
+    let height = (size / 4).max(3);
+    (0..height)
+        .map(|y| {
+            (0..size)
+                .map(|x| {
+                    let elevation = (x * 25 / (size - 1)) as i64;
+                    let dip = if y == height / 2 { 0 } else { rng.range(0, 3) };
+                    let elevation = (elevation - dip).max(0);
+                    match (x, y) {
+                        (0, 0) => 'S',
+                        (x, y) if x == size - 1 && y == height / 2 => 'E',
+                        _ => (b'a' + elevation as u8) as char,
+                    }
+                })
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+fn random_packet(rng: &mut Rng, depth: usize) -> String {
+    if depth == 0 || rng.range(0, 3) == 0 {
+        return rng.range(0, 11).to_string();
+    }
+    let items = (0..rng.range(0, 5))
+        .map(|_| random_packet(rng, depth - 1))
+        .join(",");
+    format!("[{items}]")
+}
+
+/// `size` pairs of random packets
+pub fn day13(size: usize) -> String {
+    let mut rng = Rng::new(13);
+    (0..size)
+        .map(|_| {
+            let left = random_packet(&mut rng, 4);
+            let right = random_packet(&mut rng, 4);
+            format!("[{left}]\n[{right}]")
+        })
+        .join("\n\n")
+}
+
+/// `size` random rock paths below the sand source, and a ledge under them so
+/// that some sand comes to rest even when no path is right below the source
+pub fn day14(size: usize) -> String {
+    let mut rng = Rng::new(14);
+    let depth = (size as i64).max(10);
+    let ledge = format!("{},{depth} -> {},{depth}", 500 - depth / 2, 500 + depth / 2);
+    (0..size)
+        .map(|_| {
+            let (mut x, mut y) = (rng.range(500 - depth, 500 + depth), rng.range(2, depth));
+            let mut points = vec![format!("{x},{y}")];
+            for index in 0..rng.range(1, 5) {
+                if index % 2 == 0 {
+                    x += rng.range(-5, 6);
+                } else {
+                    y = (y + rng.range(-5, 6)).max(2);
+                }
+                points.push(format!("{x},{y}"));
+            }
+            points.join(" -> ")
+        })
+        .chain([ledge])
+        .join("\n")
+}
+
+/// `size` small random sensors, all under four big sensors set diagonally
+/// around a hidden beacon, so that it is the only position of the search area
+/// not covered
+pub fn day15(size: usize) -> String {
+    const OFFSET: i64 = 4_000_000;
+    let mut rng = Rng::new(15);
+    let (x, y) = (rng.range(0, OFFSET + 1), rng.range(0, OFFSET + 1));
+    let mut lines = vec![];
+    for (dx, dy) in [(-1, -1), (-1, 1), (1, -1), (1, 1)] {
+        let (sensor_x, sensor_y) = (x + dx * OFFSET, y + dy * OFFSET);
+        let beacon_x = sensor_x - dx * (2 * OFFSET - 1);
+        lines.push(format!(
+            "Sensor at x={sensor_x}, y={sensor_y}: closest beacon is at x={beacon_x}, y={sensor_y}"
+        ));
+    }
+    while lines.len() < size + 4 {
+        let (sensor_x, sensor_y) = (rng.range(0, OFFSET + 1), rng.range(0, OFFSET + 1));
+        let radius = rng.range(1, 500_000);
+        if (sensor_x - x).abs() + (sensor_y - y).abs() <= radius {
+            continue;
+        }
+        let dx = rng.range(0, radius + 1);
+        let dy = radius - dx;
+        let (beacon_x, beacon_y) = match rng.range(0, 4) {
+            0 => (sensor_x + dx, sensor_y + dy),
+            1 => (sensor_x + dx, sensor_y - dy),
+            2 => (sensor_x - dx, sensor_y + dy),
+            _ => (sensor_x - dx, sensor_y - dy),
+        };
+        lines.push(format!(
+            "Sensor at x={sensor_x}, y={sensor_y}: closest beacon is at x={beacon_x}, y={beacon_y}"
+        ));
+    }
+    lines.join("\n")
+}
+
+fn valve_name(index: usize) -> String {
+    let letter = |value: usize| (b'A' + value as u8) as char;
+    format!("{}{}", letter(index / 26), letter(index % 26))
+}
+
+/// `size` valves with a flow rate, among 4 × `size` valves forming a random
+/// connected network of tunnels starting from AA
+pub fn day16(size: usize) -> String {
+    let mut rng = Rng::new(16);
+    let num_valves = 4 * size;
+    let mut tunnels = vec![vec![]; num_valves];
+    for valve in 1..num_valves {
+        let other = rng.range(0, valve as i64) as usize;
+        tunnels[valve].push(other);
+        tunnels[other].push(valve);
+    }
+    for _ in 0..size {
+        let (a, b) = (
+            rng.range(0, num_valves as i64),
+            rng.range(0, num_valves as i64),
+        );
+        let (a, b) = (a as usize, b as usize);
+        if a != b && !tunnels[a].contains(&b) {
+            tunnels[a].push(b);
+            tunnels[b].push(a);
+        }
+    }
+    let mut flow_rates = vec![0; num_valves];
+    let mut useful = (1..num_valves).collect_vec();
+    rng.shuffle(&mut useful);
+    for &valve in &useful[..size] {
+        flow_rates[valve] = rng.range(3, 26);
+    }
+    (0..num_valves)
+        .map(|valve| {
+            format!(
+                "Valve {} has flow rate={}; tunnels lead to valves {}",
+                valve_name(valve),
+                flow_rates[valve],
+                tunnels[valve]
+                    .iter()
+                    .map(|&other| valve_name(other))
+                    .join(", ")
+            )
+        })
+        .join("\n")
+}
+
+/// A random jet pattern of `size` jets
+pub fn day17(size: usize) -> String {
+    let mut rng = Rng::new(17);
+    (0..size)
+        .map(|_| if rng.range(0, 2) == 0 { '<' } else { '>' })
+        .collect()
+}
+
+/// `size` random lava droplet cubes, in a box whose volume grows with `size`
+pub fn day18(size: usize) -> String {
+    let mut rng = Rng::new(18);
+    let side = ((size as f64 * 2.0).cbrt() as i64).max(2);
+    (0..size)
+        .map(|_| (0..3).map(|_| rng.range(0, side)).join(","))
+        .join("\n")
+}
+
+/// `size` blueprints with costs in the same ranges as in the puzzle inputs
+pub fn day19(size: usize) -> String {
+    let mut rng = Rng::new(19);
+    (1..=size)
+        .map(|id| {
+            format!(
+                "Blueprint {id}: Each ore robot costs {} ore. Each clay robot costs {} ore. \
+                 Each obsidian robot costs {} ore and {} clay. \
+                 Each geode robot costs {} ore and {} obsidian.",
+                rng.range(2, 5),
+                rng.range(2, 5),
+                rng.range(2, 5),
+                rng.range(5, 21),
+                rng.range(2, 5),
+                rng.range(5, 21)
+            )
+        })
+        .join("\n")
+}
+
+/// `size` random numbers, including a single 0
+pub fn day20(size: usize) -> String {
+    let mut rng = Rng::new(20);
+    (0..size)
+        .map(|index| {
+            if index == size / 2 {
+                0
+            } else {
+                let value = rng.range(1, 10_000);
+                if rng.range(0, 2) == 0 {
+                    value
+                } else {
+                    -value
+                }
+            }
+        })
+        .join("\n")
+}
+
+/// About `size` monkeys forming two mirrored chains, one starting from humn
+/// and the other from a monkey yelling the same value, so the answer to part 2
+/// is humn's value. Every so often a monkey doubles the value by waiting twice
+/// for the same monkey, so the values end up overflowing 64-bit integers.
+pub fn day21(size: usize) -> String {
+    let mut rng = Rng::new(21);
+    let length = (size / 3).max(1);
+    let human = rng.range(1, 1000);
+    let mut lines = vec![
+        "root: l0 + r0".to_string(),
+        format!("humn: {human}"),
+        format!("copy: {human}"),
+        format!("l{length}: humn + c{length}"),
+        format!("r{length}: copy + c{length}"),
+    ];
+    for index in 0..=length {
+        lines.push(format!("c{index}: {}", rng.range(1, 100)));
+    }
+    for index in 0..length {
+        for side in ["l", "r"] {
+            let next = format!("{side}{}", index + 1);
+            lines.push(if index % (length / 80).max(1) == 0 {
+                format!("{side}{index}: {next} + {next}")
+            } else if index % 2 == 0 {
+                format!("{side}{index}: {next} + c{index}")
+            } else {
+                format!("{side}{index}: {next} - c{index}")
+            });
+        }
+    }
+    lines.join("\n")
+}
+
+/// A cube net shaped like the puzzle input's, with faces of `size` × `size`
+/// tiles of which about 1 in 10 is a wall, and a path of 2000 moves
+pub fn day22(size: usize) -> String {
+    let mut rng = Rng::new(22);
+    let faces = [
+        [false, true, true],
+        [false, true, false],
+        [true, true, false],
+        [true, false, false],
+    ];
+    let board = faces
+        .iter()
+        .flat_map(|row| (0..size).map(move |_| row))
+        .enumerate()
+        .map(|(y, row)| {
+            row.iter()
+                .flat_map(|&is_face| (0..size).map(move |_| is_face))
+                .enumerate()
+                .map(|(x, is_face)| match is_face {
+                    false => ' ',
+                    // keep the starting tile, on the top row, open
+                    true if y > 0 && rng.range(0, 10) == 0 => '#',
+                    true if y == 0 && x > size && rng.range(0, 10) == 0 => '#',
+                    true => '.',
+                })
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .join("\n");
+    let path = (0..1000)
+        .map(|_| {
+            let turn = if rng.range(0, 2) == 0 { 'L' } else { 'R' };
+            format!("{}{turn}", rng.range(1, size as i64 + 1))
+        })
+        .collect::<String>();
+    format!("{board}\n\n{path}{}", rng.range(1, size as i64 + 1))
+}
+
+/// A `size` × `size` grove, where half the tiles contain an elf
+pub fn day23(size: usize) -> String {
+    let mut rng = Rng::new(23);
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| if rng.range(0, 2) == 0 { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+/// A valley `size` tiles wide and `size` / 4 high, where about 3 in 4 of the
+/// tiles hold a blizzard. Like in the puzzle input, no blizzard goes up or down
+/// the columns of the entrance and the exit.
+pub fn day24(size: usize) -> String {
+    let mut rng = Rng::new(24);
+    let (width, height) = (size.max(5), (size / 4).max(4));
+    let wall = |opening: usize| {
+        (0..width)
+            .map(|x| if x == opening { '.' } else { '#' })
+            .collect::<String>()
+    };
+    let rows = (1..height - 1).map(|_| {
+        let tiles = (1..width - 1)
+            .map(|x| {
+                let is_vertical_allowed = x != 1 && x != width - 2;
+                match rng.range(0, 16) {
+                    0..=3 => '.',
+                    4..=6 => '<',
+                    7..=9 => '>',
+                    10..=12 if is_vertical_allowed => '^',
+                    13..=15 if is_vertical_allowed => 'v',
+                    _ => '.',
+                }
+            })
+            .collect::<String>();
+        format!("#{tiles}#")
+    });
+    [wall(1)]
+        .into_iter()
+        .chain(rows)
+        .chain([wall(width - 2)])
+        .join("\n")
+}
+
+/// `size` random SNAFU numbers
+pub fn day25(size: usize) -> String {
+    let mut rng = Rng::new(25);
+    (0..size)
+        .map(|_| {
+            let num_digits = rng.range(1, 20);
+            let first_digit = ["1", "2"][rng.range(0, 2) as usize];
+            let digits =
+                (1..num_digits).map(|_| ["=", "-", "0", "1", "2"][rng.range(0, 5) as usize]);
+            [first_digit].into_iter().chain(digits).collect::<String>()
+        })
+        .join("\n")
+}
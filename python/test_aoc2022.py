@@ -0,0 +1,164 @@
+"""Tests for the Python bindings.
+
+Build the extension module first, then run this script from the repository root:
+
+    cargo rustc --release --lib --features python --crate-type cdylib
+    python python/test_aoc2022.py
+"""
+
+import importlib.machinery
+import importlib.util
+import pathlib
+import sys
+import unittest
+from fractions import Fraction
+
+ROOT = pathlib.Path(__file__).resolve().parent.parent
+
+
+def load_module():
+    for name in ("libaoc2022.so", "libaoc2022.dylib", "aoc2022.dll"):
+        path = ROOT / "target" / "release" / name
+        if path.exists():
+            loader = importlib.machinery.ExtensionFileLoader("aoc2022", str(path))
+            spec = importlib.util.spec_from_file_location("aoc2022", path, loader=loader)
+            module = importlib.util.module_from_spec(spec)
+            loader.exec_module(module)
+            return module
+    sys.exit("Extension module not found, please build it first (see docstring)")
+
+
+aoc2022 = load_module()
+
+DAY01_EXAMPLE = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n"
+
+DAY12_EXAMPLE = "Sabqponm\nabcryxxl\naccszExk\nacctuvwj\nabdefghi\n"
+
+DAY21_EXAMPLE = """\
+root: pppw + sjmn
+dbpl: 5
+cczh: sllz + lgvd
+zczc: 2
+ptdq: humn - dvpt
+dvpt: 3
+lfqf: 4
+humn: 5
+ljgn: 2
+sjmn: drzm * dbpl
+sllz: 4
+pppw: cczh / lfqf
+lgvd: ljgn * ptdq
+drzm: hmdt - zczc
+hmdt: 32
+"""
+
+DAY15_EXAMPLE = """\
+Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+Sensor at x=9, y=16: closest beacon is at x=10, y=16
+Sensor at x=13, y=2: closest beacon is at x=15, y=3
+Sensor at x=12, y=14: closest beacon is at x=10, y=16
+Sensor at x=10, y=20: closest beacon is at x=10, y=16
+Sensor at x=14, y=17: closest beacon is at x=10, y=16
+Sensor at x=8, y=7: closest beacon is at x=2, y=10
+Sensor at x=2, y=0: closest beacon is at x=2, y=10
+Sensor at x=0, y=11: closest beacon is at x=2, y=10
+Sensor at x=20, y=14: closest beacon is at x=25, y=17
+Sensor at x=17, y=20: closest beacon is at x=21, y=22
+Sensor at x=16, y=7: closest beacon is at x=15, y=3
+Sensor at x=14, y=3: closest beacon is at x=15, y=3
+Sensor at x=20, y=1: closest beacon is at x=15, y=3
+"""
+
+
+class TestBindings(unittest.TestCase):
+    def test_days(self):
+        self.assertEqual(len(aoc2022.DAYS), 25)
+        self.assertEqual(aoc2022.DAYS[0], (1, "Calorie Counting"))
+
+    def test_parse_and_parts(self):
+        puzzle = aoc2022.day01.parse(DAY01_EXAMPLE)
+        self.assertEqual(puzzle.day, 1)
+        self.assertEqual(aoc2022.day01.part1(puzzle), 24000)
+        self.assertEqual(aoc2022.day01.part2(puzzle), 45000)
+        puzzle = aoc2022.day06.parse("mjqjpqmgbljsphdztnvjfqwrcgsmlb")
+        self.assertEqual(aoc2022.day06.part1(puzzle), 7)
+        self.assertEqual(aoc2022.day06.part2(puzzle), 19)
+        puzzle = aoc2022.day12.parse(DAY12_EXAMPLE)
+        self.assertEqual(aoc2022.day12.part1(puzzle), 31)
+        self.assertEqual(aoc2022.day12.part2(puzzle), (29, (0, 4)))
+
+    def test_wrong_day(self):
+        puzzle = aoc2022.day01.parse(DAY01_EXAMPLE)
+        with self.assertRaises(TypeError):
+            aoc2022.day02.part1(puzzle)
+
+    def test_solve(self):
+        result = aoc2022.solve(1, DAY01_EXAMPLE)
+        self.assertEqual(result["answers"], ["24000", "45000"])
+        self.assertEqual(set(result["timings"]), {"parse", "part1", "part2"})
+        with self.assertRaises(ValueError):
+            aoc2022.solve(26, "")
+        with self.assertRaisesRegex(ValueError, "undefined monkey bbbb"):
+            aoc2022.solve(21, "root: aaaa + bbbb\naaaa: 1\n")
+
+    def test_snafu(self):
+        examples = {1: "1", 4890: "2=-1=0", 2022: "1=11-2", 314159265: "1121-1110-1=0"}
+        for value, snafu in examples.items():
+            self.assertEqual(aoc2022.day25.to_snafu(value), snafu)
+            self.assertEqual(aoc2022.day25.parse_snafu(snafu), value)
+        with self.assertRaises(ValueError):
+            aoc2022.day25.parse_snafu("12a")
+
+    def test_monkey_riddle(self):
+        puzzle = aoc2022.day21.parse(DAY21_EXAMPLE)
+        self.assertEqual(aoc2022.day21.part1(puzzle), 152)
+        self.assertEqual(aoc2022.day21.part2(puzzle), [301])
+        self.assertEqual(aoc2022.day21.evaluate_monkey(puzzle, "sjmn"), ([150], [1]))
+        numerator, denominator = aoc2022.day21.evaluate_monkey(puzzle, "pppw", with_human=True)
+        self.assertEqual(numerator, [Fraction(-1, 2), Fraction(1, 2)])
+        self.assertEqual(denominator, [1])
+        # humn^3 = 10^15 has too large a constant term to find its integer roots
+        cubic = aoc2022.day21.parse(
+            "root: aaaa + bbbb\naaaa: cccc * humn\ncccc: humn * humn\nbbbb: 1000000000000000\nhumn: 5"
+        )
+        with self.assertRaises(ValueError):
+            aoc2022.day21.part2(cubic)
+
+    def test_sensor_field(self):
+        puzzle = aoc2022.day15.parse(DAY15_EXAMPLE)
+        self.assertEqual(aoc2022.day15.sensors_covering(puzzle, 8, 7), [6])
+        self.assertEqual(aoc2022.day15.sensors_covering(puzzle, 14, 11), [])
+        self.assertEqual(aoc2022.day15.row_coverage(puzzle, 10), [(-2, 24)])
+        self.assertEqual(aoc2022.day15.column_coverage(puzzle, 14), [(0, 10), (12, 23)])
+        self.assertEqual(aoc2022.day15.nearest_uncovered(puzzle, 14, 11), (14, 11))
+        self.assertEqual(aoc2022.day15.nearest_uncovered(puzzle, 8, 7), (-2, 7))
+        self.assertEqual(aoc2022.day15.removal_effect(puzzle, 6, 0, 20), (66, 67))
+        with self.assertRaises(ValueError):
+            aoc2022.day15.removal_effect(puzzle, 14)
+
+    def test_data_files(self):
+        for day, _ in aoc2022.DAYS:
+            path = ROOT / "data" / f"day{day:02}.txt"
+            if not path.exists():
+                continue
+            with self.subTest(day=day):
+                module = getattr(aoc2022, f"day{day:02}")
+                puzzle = module.parse(path.read_text())
+                answers = [module.part1(puzzle)]
+                if hasattr(module, "part2"):
+                    answers.append(module.part2(puzzle))
+                # lists of solutions are displayed as comma-separated values,
+                # and day 12's (steps, (x, y)) as "steps (from x,y)"
+                answers = [
+                    ", ".join(map(str, a))
+                    if isinstance(a, list)
+                    else f"{a[0]} (from {a[1][0]},{a[1][1]})"
+                    if isinstance(a, tuple)
+                    else str(a)
+                    for a in answers
+                ]
+                self.assertEqual(aoc2022.solve(day, path.read_text())["answers"], answers)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -1,16 +1,19 @@
 use itertools::Itertools;
 
-pub fn run(input: &str) {
-    let calories = input
+/// Returns the total calories carried by each elf, sorted in decreasing order
+pub fn parse(input: &str) -> Vec<i64> {
+    input
         .split("\n\n")
         .map(|s| s.lines().map(|line| line.parse::<i64>().unwrap()).sum())
         .sorted()
         .rev()
-        .collect_vec();
+        .collect_vec()
+}
 
-    let max_calories = calories[0];
-    println!("{max_calories}");
+pub fn part1(calories: &[i64]) -> i64 {
+    calories[0]
+}
 
-    let top_3_calories: i64 = calories[..3].iter().sum();
-    println!("{top_3_calories}");
+pub fn part2(calories: &[i64]) -> i64 {
+    calories[..3].iter().sum()
 }
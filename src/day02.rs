@@ -11,8 +11,8 @@ fn get_score(strategy: &[(u8, u8)], part1: bool) -> i32 {
         .sum()
 }
 
-pub fn run(input: &str) {
-    let strategy: Vec<(u8, u8)> = input
+pub fn parse(input: &str) -> Vec<(u8, u8)> {
+    input
         .lines()
         .map(|line| {
             line.split(' ')
@@ -20,10 +20,13 @@ pub fn run(input: &str) {
                 .collect_vec()
         })
         .map(|v| (v[0] - b'A', v[1] - b'X'))
-        .collect_vec();
+        .collect_vec()
+}
+
+pub fn part1(strategy: &[(u8, u8)]) -> i32 {
+    get_score(strategy, true)
+}
 
-    let score = get_score(&strategy, true);
-    println!("{}", score);
-    let score = get_score(&strategy, false);
-    println!("{}", score);
+pub fn part2(strategy: &[(u8, u8)]) -> i32 {
+    get_score(strategy, false)
 }
@@ -3,8 +3,8 @@ use itertools::Itertools;
 
 fn priority(item: u8) -> u32 {
     match item {
-        c if (b'a'..=b'z').contains(&c) => (c - b'a' + 1) as u32,
-        c if (b'A'..=b'Z').contains(&c) => (c - b'A' + 27) as u32,
+        c if c.is_ascii_lowercase() => (c - b'a' + 1) as u32,
+        c if c.is_ascii_uppercase() => (c - b'A' + 27) as u32,
         _ => unreachable!(),
     }
 }
@@ -14,7 +14,6 @@ fn find_common_item(item_groups: &[&[u8]]) -> u8 {
         .iter()
         // map to HashSets
         .map(|r| r.iter().copied().collect::<HashSet<u8>>())
-        .into_iter()
         // reduce by intersecting
         .reduce(|a, b| a.intersection(&b).copied().collect::<HashSet<u8>>())
         .unwrap()
@@ -25,7 +24,7 @@ fn find_common_item(item_groups: &[&[u8]]) -> u8 {
         .unwrap()
 }
 
-fn get_score_part1(data: &[Vec<u8>]) -> u32 {
+pub fn part1(data: &[Vec<u8>]) -> u32 {
     data.iter()
         .map(|rucksack| {
             let middle = rucksack.len() / 2;
@@ -37,7 +36,7 @@ fn get_score_part1(data: &[Vec<u8>]) -> u32 {
         .sum()
 }
 
-fn get_score_part2(data: &[Vec<u8>]) -> u32 {
+pub fn part2(data: &[Vec<u8>]) -> u32 {
     data.chunks(3)
         .map(|rucksacks| {
             let rucksacks = rucksacks.iter().map(|r| &r[..]).collect_vec();
@@ -47,13 +46,9 @@ fn get_score_part2(data: &[Vec<u8>]) -> u32 {
         .sum()
 }
 
-pub fn run(input: &str) {
-    let data = input
+pub fn parse(input: &str) -> Vec<Vec<u8>> {
+    input
         .lines()
         .map(|line| line.bytes().collect_vec())
-        .collect_vec();
-    let score = get_score_part1(&data);
-    println!("{}", score);
-    let score = get_score_part2(&data);
-    println!("{}", score);
+        .collect_vec()
 }
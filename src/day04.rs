@@ -14,13 +14,17 @@ fn overlapping(ranges: &&(RangeInclusive<i64>, RangeInclusive<i64>)) -> bool {
     ranges.0.start() <= ranges.1.end() && ranges.0.end() >= ranges.1.start()
 }
 
-pub fn run(input: &str) {
-    let ranges = parse_int_vecs(input, false)
+pub fn parse(input: &str) -> Vec<(RangeInclusive<i64>, RangeInclusive<i64>)> {
+    parse_int_vecs(input, false)
         .iter()
         .map(|values| (values[0]..=values[1], values[2]..=values[3]))
-        .collect_vec();
-    let num_contain = ranges.iter().filter(one_contains_the_other).count();
-    println!("{}", num_contain);
-    let num_overlap = ranges.iter().filter(overlapping).count();
-    println!("{}", num_overlap);
+        .collect_vec()
+}
+
+pub fn part1(ranges: &[(RangeInclusive<i64>, RangeInclusive<i64>)]) -> usize {
+    ranges.iter().filter(one_contains_the_other).count()
+}
+
+pub fn part2(ranges: &[(RangeInclusive<i64>, RangeInclusive<i64>)]) -> usize {
+    ranges.iter().filter(overlapping).count()
 }
@@ -21,7 +21,7 @@ pub fn move_crates(stacks: &mut [Vec<u8>], moves: &[Vec<i64>], one_by_one: bool)
     String::from_utf8(top_crates).unwrap()
 }
 
-pub fn run(input: &str) {
+pub fn parse(input: &str) -> (Vec<Vec<u8>>, Vec<Vec<i64>>) {
     let (stacks_str, moves_str) = input.split("\n\n").collect_tuple().unwrap();
     let mut stacks: Vec<Vec<u8>> = vec![];
     stacks_str.lines().rev().skip(1).for_each(|line| {
@@ -29,7 +29,7 @@ pub fn run(input: &str) {
             .skip(1)
             .step_by(4)
             .enumerate()
-            .filter(|(_, byte)| (b'A'..=b'Z').contains(byte))
+            .filter(|(_, byte)| byte.is_ascii_uppercase())
             .for_each(|(index, byte)| {
                 if index >= stacks.len() {
                     stacks.push(vec![])
@@ -38,9 +38,13 @@ pub fn run(input: &str) {
             });
     });
     let moves = parse_int_vecs(moves_str, false);
+    (stacks, moves)
+}
+
+pub fn part1((stacks, moves): &(Vec<Vec<u8>>, Vec<Vec<i64>>)) -> String {
+    move_crates(&mut stacks.clone(), moves, true)
+}
 
-    let top_after_one_by_one_moves = move_crates(&mut stacks.clone(), &moves, true);
-    println!("{}", top_after_one_by_one_moves);
-    let top_after_batch_moves = move_crates(&mut stacks, &moves, false);
-    println!("{}", top_after_batch_moves);
+pub fn part2((stacks, moves): &(Vec<Vec<u8>>, Vec<Vec<i64>>)) -> String {
+    move_crates(&mut stacks.clone(), moves, false)
 }
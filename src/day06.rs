@@ -11,10 +11,14 @@ fn end_index_of_window_without_duplicates(input: &[u8], length: usize) -> usize
         + length
 }
 
-pub fn run(input: &str) {
-    let input = input.bytes().collect_vec();
-    let num_chars_processed = end_index_of_window_without_duplicates(&input, 4);
-    println!("{}", num_chars_processed);
-    let num_chars_processed = end_index_of_window_without_duplicates(&input, 14);
-    println!("{}", num_chars_processed);
+pub fn parse(input: &str) -> Vec<u8> {
+    input.bytes().collect_vec()
+}
+
+pub fn part1(input: &[u8]) -> usize {
+    end_index_of_window_without_duplicates(input, 4)
+}
+
+pub fn part2(input: &[u8]) -> usize {
+    end_index_of_window_without_duplicates(input, 14)
 }
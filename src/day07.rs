@@ -1,7 +1,9 @@
 use hashbrown::HashMap;
 use itertools::Itertools;
 
-struct DirectoryContent {
+use super::repl::{arg, CommandResult};
+
+pub struct DirectoryContent {
     total_file_sizes: u64,
     subdir_names: Vec<String>,
 }
@@ -23,7 +25,7 @@ fn total_dir_size(
     content.total_file_sizes + total_subdirectory_sizes
 }
 
-pub fn run(input: &str) {
+pub fn parse(input: &str) -> HashMap<Vec<String>, DirectoryContent> {
     let commands = input.lines().collect_vec();
     let mut directory_contents: HashMap<Vec<String>, DirectoryContent> = HashMap::new();
     let mut current_path: Vec<String> = vec![]; // default to root
@@ -67,33 +69,98 @@ pub fn run(input: &str) {
             }
             _ => {
                 // $ cd {subdirectory name}
-                let subdir_name = command.split(' ').last().unwrap().to_string();
+                let subdir_name = command.split(' ').next_back().unwrap().to_string();
                 current_path.push(subdir_name);
                 index += 1;
             }
         }
     }
-    let directory_sizes = directory_contents
+    directory_contents
+}
+
+fn all_directory_sizes(directory_contents: &HashMap<Vec<String>, DirectoryContent>) -> Vec<u64> {
+    directory_contents
         .keys()
-        .map(|directory_path| total_dir_size(&directory_contents, directory_path))
-        .collect_vec();
+        .map(|directory_path| total_dir_size(directory_contents, directory_path))
+        .collect_vec()
+}
 
-    // part 1
-    let total_size_of_small_directories: u64 = directory_sizes
-        .iter()
-        .filter(|&&size| size <= 100_000)
-        .sum();
-    println!("{}", total_size_of_small_directories);
+fn total_size_of_small_directories(
+    directory_contents: &HashMap<Vec<String>, DirectoryContent>,
+    max_size: u64,
+) -> u64 {
+    all_directory_sizes(directory_contents)
+        .into_iter()
+        .filter(|&size| size <= max_size)
+        .sum()
+}
 
-    // part 2
-    let total_disk_space = 70_000_000;
-    let needed = 30_000_000;
-    let available_size = total_disk_space - total_dir_size(&directory_contents, &vec![]);
-    let minimum_to_delete = needed - available_size;
-    let size_of_smallest_directory_to_delete = directory_sizes
-        .iter()
-        .filter(|&&size| size >= minimum_to_delete)
+fn size_of_smallest_directory_to_delete(
+    directory_contents: &HashMap<Vec<String>, DirectoryContent>,
+    total_disk_space: u64,
+    needed: u64,
+) -> Option<u64> {
+    let used_size = total_dir_size(directory_contents, &vec![]);
+    let available_size = total_disk_space.saturating_sub(used_size);
+    let minimum_to_delete = needed.saturating_sub(available_size);
+    all_directory_sizes(directory_contents)
+        .into_iter()
+        .filter(|&size| size >= minimum_to_delete)
         .min()
-        .unwrap();
-    println!("{}", size_of_smallest_directory_to_delete);
+}
+
+pub fn part1(directory_contents: &HashMap<Vec<String>, DirectoryContent>) -> u64 {
+    total_size_of_small_directories(directory_contents, 100_000)
+}
+
+pub fn part2(directory_contents: &HashMap<Vec<String>, DirectoryContent>) -> u64 {
+    size_of_smallest_directory_to_delete(directory_contents, 70_000_000, 30_000_000).unwrap()
+}
+
+pub const REPL_HELP: &str = "\
+sizes [min_size]  list the total size of each directory
+size [path]       show the total size of a directory, e.g. 'size /a/e'
+part1 [max_size]  total size of the directories of at most max_size (default 100000)
+part2 [disk] [needed]
+                  smallest directory to delete (default 70000000 30000000)";
+
+pub fn repl_command(
+    directory_contents: &mut HashMap<Vec<String>, DirectoryContent>,
+    command: &str,
+    args: &[&str],
+) -> CommandResult {
+    let result = match command {
+        "sizes" => arg(args, 0, Some(0)).map(|min_size| {
+            directory_contents
+                .keys()
+                .sorted()
+                .map(|path| (path, total_dir_size(directory_contents, path)))
+                .filter(|&(_, size)| size >= min_size)
+                .map(|(path, size)| format!("{size:>10} /{}", path.join("/")))
+                .join("\n")
+        }),
+        "size" => arg(args, 0, Some("/".to_string())).and_then(|path| {
+            let path = path
+                .split('/')
+                .filter(|name| !name.is_empty())
+                .map(String::from)
+                .collect_vec();
+            if directory_contents.contains_key(&path) {
+                Ok(total_dir_size(directory_contents, &path).to_string())
+            } else {
+                Err(format!("unknown directory /{}", path.join("/")))
+            }
+        }),
+        "part1" => arg(args, 0, Some(100_000)).map(|max_size| {
+            total_size_of_small_directories(directory_contents, max_size).to_string()
+        }),
+        "part2" => arg(args, 0, Some(70_000_000)).and_then(|total_disk_space| {
+            let needed = arg(args, 1, Some(30_000_000))?;
+            size_of_smallest_directory_to_delete(directory_contents, total_disk_space, needed)
+                .map(|size| size.to_string())
+                .ok_or_else(|| "no directory is large enough".to_string())
+        }),
+        _ => return None,
+    };
+    Some(result)
 }
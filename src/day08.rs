@@ -27,7 +27,7 @@ fn is_visible_tree(forest: &[Vec<u8>], x: usize, y: usize) -> bool {
         .any(|(dx, dy)| distance_to_tree_or_border(forest, x, y, dx, dy) <= 0)
 }
 
-fn num_visible_trees(forest: &[Vec<u8>]) -> usize {
+pub fn part1(forest: &[Vec<u8>]) -> usize {
     (0..forest[0].len())
         .cartesian_product(0..forest.len())
         .filter(|&(x, y)| is_visible_tree(forest, x, y))
@@ -40,7 +40,7 @@ fn scenic_score(forest: &[Vec<u8>], x: usize, y: usize) -> i32 {
     })
 }
 
-fn max_scenic_score(forest: &[Vec<u8>]) -> i32 {
+pub fn part2(forest: &[Vec<u8>]) -> i32 {
     (0..forest[0].len())
         .cartesian_product(0..forest.len())
         .map(|(x, y)| scenic_score(forest, x, y))
@@ -48,15 +48,9 @@ fn max_scenic_score(forest: &[Vec<u8>]) -> i32 {
         .unwrap()
 }
 
-pub fn run(input: &str) {
-    let forest: Vec<Vec<u8>> = input
+pub fn parse(input: &str) -> Vec<Vec<u8>> {
+    input
         .lines()
         .map(|line| line.bytes().map(|c| c - b'0').collect_vec())
-        .collect_vec();
-
-    let num = num_visible_trees(&forest);
-    println!("{}", num);
-
-    let score = max_scenic_score(&forest);
-    println!("{}", score);
+        .collect_vec()
 }
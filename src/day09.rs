@@ -33,8 +33,8 @@ fn simulate(moves: &[(u8, i32)], num_knots: usize) -> usize {
     visited.len()
 }
 
-pub fn run(input: &str) {
-    let moves: Vec<(u8, i32)> = input
+pub fn parse(input: &str) -> Vec<(u8, i32)> {
+    input
         .lines()
         .map(|line| {
             let (direction, distance) = line.split(' ').collect_tuple::<(&str, &str)>().unwrap();
@@ -43,11 +43,13 @@ pub fn run(input: &str) {
                 distance.parse::<i32>().unwrap(),
             )
         })
-        .collect_vec();
+        .collect_vec()
+}
 
-    let num_visited = simulate(&moves, 2);
-    println!("{}", num_visited);
+pub fn part1(moves: &[(u8, i32)]) -> usize {
+    simulate(moves, 2)
+}
 
-    let num_visited = simulate(&moves, 10);
-    println!("{}", num_visited);
+pub fn part2(moves: &[(u8, i32)]) -> usize {
+    simulate(moves, 10)
 }
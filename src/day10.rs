@@ -1,26 +1,32 @@
-fn total_signal_strength(x_values: &[i32]) -> i32 {
+use itertools::Itertools;
+
+pub fn part1(x_values: &[i32]) -> i32 {
     (20..=220)
         .step_by(40)
         .map(|cycle| cycle as i32 * x_values[cycle - 1])
         .sum()
 }
 
-fn render_image(x_values: &[i32]) {
-    for y in 0..6 {
-        for x in 0..40 {
-            let cycle = y * 40 + x;
-            let x_val = x_values[cycle as usize];
-            if ((x - 1)..=(x + 1)).contains(&x_val) {
-                print!("█");
-            } else {
-                print!(" ");
-            }
-        }
-        println!();
-    }
+/// Renders the CRT image, one line per row
+pub fn part2(x_values: &[i32]) -> String {
+    (0..6)
+        .map(|y| {
+            (0..40)
+                .map(|x| {
+                    let cycle = y * 40 + x;
+                    let x_val = x_values[cycle as usize];
+                    if ((x - 1)..=(x + 1)).contains(&x_val) {
+                        '█'
+                    } else {
+                        ' '
+                    }
+                })
+                .collect::<String>()
+        })
+        .join("\n")
 }
 
-pub fn run(input: &str) {
+pub fn parse(input: &str) -> Vec<i32> {
     let mut x = 1;
     let mut x_values = vec![x];
     input.lines().for_each(|operation| {
@@ -28,15 +34,16 @@ pub fn run(input: &str) {
         match operation {
             "noop" => {}
             _ => {
-                let value = operation.split(' ').last().unwrap().parse::<i32>().unwrap();
+                let value = operation
+                    .split(' ')
+                    .next_back()
+                    .unwrap()
+                    .parse::<i32>()
+                    .unwrap();
                 x += value;
                 x_values.push(x);
             }
         }
     });
-
-    let signal_strength = total_signal_strength(&x_values);
-    println!("{}", signal_strength);
-
-    render_image(&x_values);
+    x_values
 }
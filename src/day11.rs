@@ -1,7 +1,7 @@
 use regex::Regex;
 
 #[derive(Clone)]
-struct Monkey {
+pub struct Monkey {
     items: Vec<u64>,
     val1: Option<u64>,
     is_add: bool, // alternatively: mul
@@ -40,7 +40,7 @@ fn monkey_business(monkeys: &[Monkey], num_rounds: usize, modulus: u64, divide_b
     monkeys[0].num_inspections * monkeys[1].num_inspections
 }
 
-pub fn run(input: &str) {
+pub fn parse(input: &str) -> Vec<Monkey> {
     let regex = Regex::new(
         r"Monkey (\d+):
   Starting items: (.*?)
@@ -50,7 +50,7 @@ pub fn run(input: &str) {
     If false: throw to monkey (\d+)",
     )
     .unwrap();
-    let monkeys: Vec<Monkey> = regex
+    regex
         .captures_iter(input)
         .map(|cap| Monkey {
             items: cap[2]
@@ -65,13 +65,17 @@ pub fn run(input: &str) {
             monkey_if_false: cap[8].parse().unwrap(),
             num_inspections: 0,
         })
-        .collect();
+        .collect()
+}
 
-    let modulus = monkeys.iter().map(|monkey| monkey.divisible_by).product();
+fn common_modulus(monkeys: &[Monkey]) -> u64 {
+    monkeys.iter().map(|monkey| monkey.divisible_by).product()
+}
 
-    let level = monkey_business(&monkeys, 20, modulus, true);
-    println!("{}", level);
+pub fn part1(monkeys: &[Monkey]) -> u64 {
+    monkey_business(monkeys, 20, common_modulus(monkeys), true)
+}
 
-    let level = monkey_business(&monkeys, 10_000, modulus, false);
-    println!("{}", level);
+pub fn part2(monkeys: &[Monkey]) -> u64 {
+    monkey_business(monkeys, 10_000, common_modulus(monkeys), false)
 }
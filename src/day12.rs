@@ -1,53 +1,226 @@
+use super::repl::{arg, CommandResult};
 use itertools::Itertools;
-use pathfinding::directed::bfs::bfs;
-use std::hash::Hash;
-
-/// For part 2, using BFS independently for each low position is *very* slow.
-/// Instead, it's best to run BFS just once: to do this, we can use a starting
-/// position named AnyLowPosition, whose successors are all the low positions.
-/// This makes the code more verbose since it needs to handle the enum cases in
-/// a few places, but it's almost two orders of magnitude faster.
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
-enum Pos {
-    AnyLowPosition,
-    Position(i32, i32),
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::sync::OnceLock;
+
+/// Shades of the ASCII heatmap, from the closest to the farthest from E
+const HEATMAP_SHADES: &[u8] = b"@%*+=-:.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathError {
+    OutOfBounds(i32, i32),
+    Unreachable(i32, i32),
+    NoReachableLowSquare,
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathError::OutOfBounds(x, y) => write!(f, "{x},{y} is outside of the heightmap"),
+            PathError::Unreachable(x, y) => write!(f, "E can't be reached from {x},{y}"),
+            PathError::NoReachableLowSquare => {
+                write!(f, "E can't be reached from any square of elevation a")
+            }
+        }
+    }
+}
+
+pub struct Heightmap {
+    signal_map: Vec<Vec<u8>>,
+    low_positions: Vec<(i32, i32)>,
+    start_pos: (i32, i32),
+    target_pos: (i32, i32),
+    /// distances[y][x] is the number of steps from (x, y) to E, if reachable.
+    /// Computed on first use, so that the search counts as solving, not parsing.
+    distances: OnceLock<Vec<Vec<Option<u32>>>>,
 }
-use Pos::*;
-
-fn shortest_distance(
-    signal_map: &[Vec<u8>],
-    low_positions: &[Pos], // only used in part 2
-    start_pos: Pos,
-    target_pos: Pos,
-) -> u32 {
+
+fn neighbors(signal_map: &[Vec<u8>], (x, y): (i32, i32)) -> impl Iterator<Item = (i32, i32)> + '_ {
     let height = signal_map.len() as i32;
     let width = signal_map[0].len() as i32;
-    let successors = |&pos: &Pos| match pos {
-        AnyLowPosition => low_positions.to_vec(),
-        Position(x, y) => {
-            let max_signal: u8 = signal_map[y as usize][x as usize] + 1;
-            [(-1, 0), (1, 0), (0, -1), (0, 1)]
-                .into_iter()
-                .map(|(dx, dy)| (x + dx, y + dy))
-                .filter(|&(nx, ny)| {
-                    nx >= 0
-                        && ny >= 0
-                        && nx < width
-                        && ny < height
-                        && (b'a'..=max_signal).contains(&signal_map[ny as usize][nx as usize])
+    [(-1, 0), (1, 0), (0, -1), (0, 1)]
+        .into_iter()
+        .map(move |(dx, dy)| (x + dx, y + dy))
+        .filter(move |&(nx, ny)| nx >= 0 && ny >= 0 && nx < width && ny < height)
+}
+
+/// Whether we can climb from one square to the next (at most one higher)
+fn can_step(signal_map: &[Vec<u8>], (x, y): (i32, i32), (nx, ny): (i32, i32)) -> bool {
+    signal_map[ny as usize][nx as usize] <= signal_map[y as usize][x as usize] + 1
+}
+
+/// A single BFS backwards from the target, following the steps in reverse, so
+/// the distance to the target from every square is known at once. Both parts
+/// (and the distance from any other start) are then just lookups.
+fn distances_to(signal_map: &[Vec<u8>], target_pos: (i32, i32)) -> Vec<Vec<Option<u32>>> {
+    let mut distances = signal_map
+        .iter()
+        .map(|row| vec![None; row.len()])
+        .collect_vec();
+    distances[target_pos.1 as usize][target_pos.0 as usize] = Some(0);
+    let mut queue = VecDeque::from([(target_pos, 0)]);
+    while let Some((pos, distance)) = queue.pop_front() {
+        for prev in neighbors(signal_map, pos) {
+            let cell = &mut distances[prev.1 as usize][prev.0 as usize];
+            if cell.is_none() && can_step(signal_map, prev, pos) {
+                *cell = Some(distance + 1);
+                queue.push_back((prev, distance + 1));
+            }
+        }
+    }
+    distances
+}
+
+impl Heightmap {
+    fn distances(&self) -> &Vec<Vec<Option<u32>>> {
+        self.distances
+            .get_or_init(|| distances_to(&self.signal_map, self.target_pos))
+    }
+
+    /// The number of steps from the given square to E
+    pub fn distance_from(&self, (x, y): (i32, i32)) -> Result<u32, PathError> {
+        self.distances()
+            .get(y as usize)
+            .and_then(|row| row.get(x as usize))
+            .ok_or(PathError::OutOfBounds(x, y))?
+            .ok_or(PathError::Unreachable(x, y))
+    }
+
+    /// The shortest path from the given square to E, found by walking down the
+    /// distance field
+    pub fn path_from(&self, start: (i32, i32)) -> Result<Vec<(i32, i32)>, PathError> {
+        let mut distance = self.distance_from(start)?;
+        let mut path = vec![start];
+        let mut pos = start;
+        while distance > 0 {
+            pos = neighbors(&self.signal_map, pos)
+                .find(|&next| {
+                    self.distance_from(next) == Ok(distance - 1)
+                        && can_step(&self.signal_map, pos, next)
                 })
-                .map(|(x, y)| Position(x, y))
-                .collect_vec()
+                .unwrap();
+            distance -= 1;
+            path.push(pos);
         }
-    };
-    let success = |&pos: &Pos| pos == target_pos;
-    bfs(&start_pos, successors, success).unwrap().len() as u32 - 1
+        Ok(path)
+    }
+
+    /// The shortest path from S to E
+    pub fn path_from_start(&self) -> Result<Vec<(i32, i32)>, PathError> {
+        self.path_from(self.start_pos)
+    }
+
+    /// The square of elevation a that is the closest to E
+    pub fn best_start(&self) -> Result<(i32, i32), PathError> {
+        self.low_positions
+            .iter()
+            .copied()
+            .filter(|&pos| self.distance_from(pos).is_ok())
+            .min_by_key(|&pos| self.distance_from(pos).unwrap())
+            .ok_or(PathError::NoReachableLowSquare)
+    }
+
+    /// The shortest path to E from any square of elevation a (its first
+    /// position is the starting square that won)
+    pub fn best_path(&self) -> Result<Vec<(i32, i32)>, PathError> {
+        self.path_from(self.best_start()?)
+    }
+
+    /// The squares from which E can't be reached, row by row
+    pub fn unreachable(&self) -> Vec<(i32, i32)> {
+        self.distances()
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .positions(Option::is_none)
+                    .map(move |x| (x as i32, y as i32))
+            })
+            .collect_vec()
+    }
+
+    fn max_distance(&self) -> u32 {
+        self.distances()
+            .iter()
+            .flatten()
+            .flatten()
+            .copied()
+            .max()
+            .unwrap()
+    }
+
+    /// The distance field as text: the closer to E, the darker the shade, and
+    /// the squares from which E can't be reached are marked with X.
+    pub fn heatmap(&self) -> String {
+        let max_distance = self.max_distance() as usize;
+        self.distances()
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|distance| match distance {
+                        Some(0) => 'E',
+                        Some(distance) => {
+                            let shade = *distance as usize * (HEATMAP_SHADES.len() - 1)
+                                / max_distance.max(1);
+                            HEATMAP_SHADES[shade] as char
+                        }
+                        None => 'X',
+                    })
+                    .collect::<String>()
+            })
+            .join("\n")
+    }
+
+    /// The distance field as a grayscale PGM image: the closer to E, the
+    /// brighter, and black for the squares from which E can't be reached.
+    pub fn heatmap_pgm(&self) -> String {
+        let max_distance = self.max_distance().max(1);
+        let pixels = self
+            .distances()
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|distance| match distance {
+                        Some(distance) => 255 - distance * 254 / max_distance,
+                        None => 0,
+                    })
+                    .join(" ")
+            })
+            .join("\n");
+        let (width, height) = (self.signal_map[0].len(), self.signal_map.len());
+        format!("P2\n{width} {height}\n255\n{pixels}\n")
+    }
+
+    /// Draws the path over the heightmap like in the puzzle statement: each
+    /// step is an arrow pointing to the next square, E marks the end, and the
+    /// squares off the path are dots.
+    pub fn render_path(&self, path: &[(i32, i32)]) -> String {
+        let mut canvas = self
+            .signal_map
+            .iter()
+            .map(|row| vec!['.'; row.len()])
+            .collect_vec();
+        for (&(x, y), &(nx, ny)) in path.iter().tuple_windows() {
+            canvas[y as usize][x as usize] = match (nx - x, ny - y) {
+                (1, 0) => '>',
+                (-1, 0) => '<',
+                (0, 1) => 'v',
+                _ => '^',
+            };
+        }
+        if let Some(&(x, y)) = path.last() {
+            canvas[y as usize][x as usize] = 'E';
+        }
+        canvas.iter().map(|row| row.iter().join("")).join("\n")
+    }
 }
 
-pub fn run(input: &str) {
-    let mut start_pos: Option<Pos> = None;
-    let mut target_pos: Option<Pos> = None;
-    let mut low_positions: Vec<Pos> = vec![];
+pub fn parse(input: &str) -> Heightmap {
+    let mut start_pos = None;
+    let mut target_pos = None;
+    let mut low_positions = vec![];
     let signal_map = input
         .lines()
         .enumerate()
@@ -58,14 +231,14 @@ pub fn run(input: &str) {
                     let (x, y) = (x as i32, y as i32);
                     match c {
                         b'a' | b'S' => {
-                            low_positions.push(Position(x, y));
+                            low_positions.push((x, y));
                             if c == b'S' {
-                                start_pos = Some(Position(x, y));
+                                start_pos = Some((x, y));
                             }
                             b'a'
                         }
                         b'E' => {
-                            target_pos = Some(Position(x, y));
+                            target_pos = Some((x, y));
                             b'z'
                         }
                         c => c,
@@ -75,11 +248,113 @@ pub fn run(input: &str) {
         })
         .collect_vec();
 
-    let (start_pos, target_pos) = (start_pos.unwrap(), target_pos.unwrap());
+    Heightmap {
+        signal_map,
+        low_positions,
+        start_pos: start_pos.unwrap(),
+        target_pos: target_pos.unwrap(),
+        distances: OnceLock::new(),
+    }
+}
+
+pub fn part1(heightmap: &Heightmap) -> u32 {
+    heightmap
+        .distance_from(heightmap.start_pos)
+        .unwrap_or_else(|error| panic!("{error}"))
+}
+
+/// The answer to part 2, along with the starting square that won
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BestStart {
+    pub steps: u32,
+    pub start: (i32, i32),
+}
+
+impl fmt::Display for BestStart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (x, y) = self.start;
+        write!(f, "{} (from {x},{y})", self.steps)
+    }
+}
+
+pub fn part2(heightmap: &Heightmap) -> BestStart {
+    heightmap
+        .best_start()
+        .and_then(|start| {
+            let steps = heightmap.distance_from(start)?;
+            Ok(BestStart { steps, start })
+        })
+        .unwrap_or_else(|error| panic!("{error}"))
+}
+
+pub const REPL_HELP: &str = "\
+path [x,y]        the shortest path from S (or the given square) to E, drawn over the heightmap
+best              the shortest path from any square of elevation a, and where it starts
+steps <x,y>       the number of steps from the given square to E
+heatmap [path]    the distance to E from every square, or save it as a PGM image
+unreachable       the squares from which E can't be reached";
 
-    let length = shortest_distance(&signal_map, &low_positions, start_pos, target_pos);
-    println!("{}", length);
+fn parse_position(args: &[&str]) -> Result<(i32, i32), String> {
+    let position: String = arg(args, 0, None)?;
+    position
+        .split(',')
+        .map(|value| value.trim().parse().ok())
+        .collect_tuple()
+        .and_then(|(x, y)| Some((x?, y?)))
+        .ok_or_else(|| format!("invalid position: {position}, expected x,y"))
+}
+
+fn describe_path(heightmap: &Heightmap, path: &[(i32, i32)]) -> String {
+    let (x, y) = path[0];
+    format!(
+        "{} steps from {x},{y}\n{}",
+        path.len() - 1,
+        heightmap.render_path(path)
+    )
+}
 
-    let length = shortest_distance(&signal_map, &low_positions, AnyLowPosition, target_pos) - 1;
-    println!("{}", length);
+pub fn repl_command(heightmap: &mut Heightmap, command: &str, args: &[&str]) -> CommandResult {
+    let result = match command {
+        "path" => {
+            let start = match args.is_empty() {
+                true => Ok(heightmap.start_pos),
+                false => parse_position(args),
+            };
+            start.and_then(|start| {
+                heightmap
+                    .path_from(start)
+                    .map(|path| describe_path(heightmap, &path))
+                    .map_err(|error| error.to_string())
+            })
+        }
+        "best" => heightmap
+            .best_path()
+            .map(|path| describe_path(heightmap, &path))
+            .map_err(|error| error.to_string()),
+        "steps" => parse_position(args).and_then(|start| {
+            heightmap
+                .distance_from(start)
+                .map(|distance| distance.to_string())
+                .map_err(|error| error.to_string())
+        }),
+        "heatmap" => match args.first() {
+            Some(path) => fs::write(path, heightmap.heatmap_pgm())
+                .map(|_| format!("saved to {path}"))
+                .map_err(|error| error.to_string()),
+            None => Ok(heightmap.heatmap()),
+        },
+        "unreachable" => {
+            let unreachable = heightmap.unreachable();
+            Ok(format!(
+                "{} squares: {}",
+                unreachable.len(),
+                unreachable
+                    .iter()
+                    .map(|(x, y)| format!("{x},{y}"))
+                    .join(" ")
+            ))
+        }
+        _ => return None,
+    };
+    Some(result)
 }
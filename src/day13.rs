@@ -5,7 +5,7 @@ use std::cmp::{Ord, Ordering};
 
 #[derive(Debug, Eq, PartialEq, Clone, Deserialize)]
 #[serde(untagged)]
-enum Packet {
+pub enum Packet {
     Integer(i64),
     List(Vec<Packet>),
 }
@@ -49,7 +49,7 @@ impl PartialOrd for Packet {
     }
 }
 
-fn sum_good_indices(packets: &[Packet]) -> usize {
+pub fn part1(packets: &[Packet]) -> usize {
     packets
         .chunks(2)
         .map(|pair| pair[0].cmp(&pair[1]))
@@ -59,7 +59,7 @@ fn sum_good_indices(packets: &[Packet]) -> usize {
         .sum()
 }
 
-fn decoder_key(packets: &[Packet]) -> usize {
+pub fn part2(packets: &[Packet]) -> usize {
     let mut packets = packets.to_vec();
     let divider_packet1: Packet = from_str("[[2]]").unwrap();
     let divider_packet2: Packet = from_str("[[6]]").unwrap();
@@ -72,16 +72,10 @@ fn decoder_key(packets: &[Packet]) -> usize {
         .product()
 }
 
-pub fn run(input: &str) {
-    let packets: Vec<Packet> = input
+pub fn parse(input: &str) -> Vec<Packet> {
+    input
         .lines()
         .filter(|line| !line.is_empty())
         .map(|line| from_str(line).unwrap())
-        .collect_vec();
-
-    let sum = sum_good_indices(&packets);
-    println!("{}", sum);
-
-    let key = decoder_key(&packets);
-    println!("{}", key);
+        .collect_vec()
 }
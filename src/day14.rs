@@ -1,7 +1,9 @@
+use super::repl::{arg, CommandResult};
 use hashbrown::HashSet;
 use itertools::Itertools;
+use std::fs;
 
-type Cave = HashSet<(i32, i32)>;
+pub type Cave = HashSet<(i32, i32)>;
 
 fn make_cave(segments: &[Vec<(i32, i32)>]) -> Cave {
     let mut cave = HashSet::new();
@@ -24,7 +26,272 @@ fn make_cave(segments: &[Vec<(i32, i32)>]) -> Cave {
     cave
 }
 
-fn num_units_before_flow_out(cave: &mut Cave, with_floor: bool) -> usize {
+/// Where the sand comes from, and whether there's a floor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandConfig {
+    /// the grains are poured from each source in turn
+    pub sources: Vec<(i32, i32)>,
+    /// if set, the floor is this far below the lowest rock (2 in the puzzle),
+    /// otherwise the sand flows out into the abyss
+    pub floor_offset: Option<i32>,
+}
+
+impl Default for SandConfig {
+    fn default() -> Self {
+        Self {
+            sources: vec![(500, 0)],
+            floor_offset: None,
+        }
+    }
+}
+
+impl SandConfig {
+    pub fn with_floor() -> Self {
+        Self {
+            floor_offset: Some(2),
+            ..Self::default()
+        }
+    }
+}
+
+/// Grains fall down if possible, else down-left, else down-right
+const MOVES: [i32; 3] = [0, -1, 1];
+
+/// The cave as a dense grid, large enough to hold all the sand that can come
+/// to rest (sand spreads by at most one column per row)
+struct Grid {
+    min_x: i32,
+    min_y: i32,
+    width: usize,
+    /// the lowest row where sand can come to rest
+    max_y: i32,
+    /// the row of the floor, if any
+    floor_y: Option<i32>,
+    is_blocked: Vec<bool>,
+}
+
+impl Grid {
+    fn new(cave: &Cave, config: &SandConfig) -> Self {
+        let rocks_max_y = cave.iter().map(|&(_, y)| y).max().unwrap();
+        let floor_y = config.floor_offset.map(|offset| rocks_max_y + offset);
+        let max_y = floor_y.map_or(rocks_max_y, |floor_y| floor_y - 1);
+        let points = cave.iter().chain(&config.sources).collect_vec();
+        let min_y = points.iter().map(|&&(_, y)| y).min().unwrap();
+        let depth = max_y - min_y + 1;
+        let min_x = points.iter().map(|&&(x, _)| x).min().unwrap() - depth - 1;
+        let max_x = points.iter().map(|&&(x, _)| x).max().unwrap() + depth + 1;
+        let width = (max_x - min_x + 1) as usize;
+        let mut grid = Self {
+            min_x,
+            min_y,
+            width,
+            max_y,
+            floor_y,
+            is_blocked: vec![false; width * (max_y - min_y + 1).max(0) as usize],
+        };
+        for &(x, y) in cave.iter().filter(|&&(_, y)| y <= max_y) {
+            let index = grid.index(x, y);
+            grid.is_blocked[index] = true;
+        }
+        grid
+    }
+
+    fn index(&self, x: i32, y: i32) -> usize {
+        (y - self.min_y) as usize * self.width + (x - self.min_x) as usize
+    }
+
+    /// Whether the cell holds rock or sand. Below the grid, the cells are all
+    /// blocked if there's a floor, and all free otherwise.
+    fn is_blocked(&self, x: i32, y: i32) -> bool {
+        if y > self.max_y {
+            self.floor_y.is_some()
+        } else {
+            self.is_blocked[self.index(x, y)]
+        }
+    }
+}
+
+/// Pours the grains one by one, until one of them flows out into the abyss or
+/// every source is blocked, and returns the number of grains at rest, along
+/// with the trajectory of the grain that flowed out, if any. Each source keeps
+/// the path of its last grain as a stack, so the next grain resumes from where
+/// the previous one came to rest, instead of falling all the way from the
+/// source. Whenever a grain comes to rest, `on_rest` is called with its full
+/// trajectory, from its source to its resting place.
+pub fn pour_sand(
+    cave: &Cave,
+    config: &SandConfig,
+    mut on_rest: impl FnMut(&[(i32, i32)]),
+) -> (usize, Option<Vec<(i32, i32)>>) {
+    let mut grid = Grid::new(cave, config);
+    let mut paths = config
+        .sources
+        .iter()
+        .filter(|&&(x, y)| !grid.is_blocked(x, y))
+        .map(|&source| vec![source])
+        .collect_vec();
+    let mut num_units = 0;
+    while !paths.is_empty() {
+        for path in &mut paths {
+            // the grains from other sources may have filled the end of the path
+            while path.last().is_some_and(|&(x, y)| grid.is_blocked(x, y)) {
+                path.pop();
+            }
+            while let Some(&(x, y)) = path.last() {
+                if y >= grid.max_y && grid.floor_y.is_none() {
+                    return (num_units, Some(path.clone())); // flows out
+                }
+                let next = MOVES
+                    .iter()
+                    .map(|dx| (x + dx, y + 1))
+                    .find(|&(nx, ny)| !grid.is_blocked(nx, ny));
+                match next {
+                    Some(next) => path.push(next),
+                    None => {
+                        let index = grid.index(x, y);
+                        grid.is_blocked[index] = true;
+                        on_rest(path);
+                        path.pop();
+                        num_units += 1;
+                        break;
+                    }
+                }
+            }
+        }
+        paths.retain(|path| !path.is_empty());
+    }
+    (num_units, None)
+}
+
+pub fn count_resting_sand(cave: &Cave, config: &SandConfig) -> usize {
+    pour_sand(cave, config, |_| {}).0
+}
+
+/// Draws the cave, cropped to the rock, sand, sources and trajectory: `#` for
+/// rock (including the floor, if any), `o` for sand at rest, `+` for the
+/// sources, and `~` for the trajectory of a falling grain.
+pub fn render(
+    cave: &Cave,
+    config: &SandConfig,
+    sand: &[(i32, i32)],
+    trajectory: &[(i32, i32)],
+) -> String {
+    let floor_y = config
+        .floor_offset
+        .map(|offset| cave.iter().map(|&(_, y)| y).max().unwrap() + offset);
+    let points = cave
+        .iter()
+        .chain(sand)
+        .chain(&config.sources)
+        .chain(trajectory)
+        .collect_vec();
+    let (min_x, max_x) = points
+        .iter()
+        .map(|&&(x, _)| x)
+        .minmax()
+        .into_option()
+        .unwrap();
+    let (min_y, max_y) = points
+        .iter()
+        .map(|&&(_, y)| y)
+        .minmax()
+        .into_option()
+        .unwrap();
+    let max_y = floor_y.map_or(max_y, |floor_y| max_y.max(floor_y));
+    let sand: HashSet<_> = sand.iter().collect();
+    let trajectory: HashSet<_> = trajectory.iter().collect();
+    (min_y..=max_y)
+        .map(|y| {
+            (min_x..=max_x)
+                .map(|x| {
+                    if cave.contains(&(x, y)) || Some(y) == floor_y {
+                        '#'
+                    } else if sand.contains(&(x, y)) {
+                        'o'
+                    } else if config.sources.contains(&(x, y)) {
+                        '+'
+                    } else if trajectory.contains(&(x, y)) {
+                        '~'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+/// The sand at rest once the pouring stops, along with the trajectory of the
+/// grain that flowed out, if any
+pub fn final_state(cave: &Cave, config: &SandConfig) -> String {
+    let mut sand = vec![];
+    let (_, outflow) = pour_sand(cave, config, |path| sand.push(*path.last().unwrap()));
+    render(cave, config, &sand, &outflow.unwrap_or_default())
+}
+
+/// Draws the cave every `every` grains, optionally with the trajectory of the
+/// last grain, and one last time when the pouring stops.
+pub fn frames(
+    cave: &Cave,
+    config: &SandConfig,
+    every: usize,
+    with_trajectory: bool,
+) -> Vec<String> {
+    assert!(
+        every > 0,
+        "the number of grains between frames must be positive"
+    );
+    let mut sand = vec![];
+    let mut frames = vec![];
+    let (num_units, outflow) = pour_sand(cave, config, |path| {
+        sand.push(*path.last().unwrap());
+        if sand.len() % every == 0 {
+            let trajectory = if with_trajectory { path } else { &[] };
+            let frame = render(cave, config, &sand, trajectory);
+            frames.push(format!("After {} grains:\n{frame}", sand.len()));
+        }
+    });
+    let trajectory = outflow.filter(|_| with_trajectory).unwrap_or_default();
+    let frame = render(cave, config, &sand, &trajectory);
+    frames.push(format!("Final state, {num_units} grains at rest:\n{frame}"));
+    frames
+}
+
+/// With a floor, the sand ends up filling every cell it can reach from the
+/// sources, so we just count them, row by row, without pouring any grain.
+/// Panics if there's no floor.
+pub fn count_reachable_cells(cave: &Cave, config: &SandConfig) -> usize {
+    assert!(config.floor_offset.is_some(), "the sand flows out forever");
+    let grid = Grid::new(cave, config);
+    let mut reachable = vec![false; grid.is_blocked.len()];
+    for &(x, y) in &config.sources {
+        if y <= grid.max_y && !grid.is_blocked(x, y) {
+            reachable[grid.index(x, y)] = true;
+        }
+    }
+    let mut num_units = 0;
+    for y in grid.min_y..=grid.max_y {
+        for x in grid.min_x..grid.min_x + grid.width as i32 {
+            if !reachable[grid.index(x, y)] {
+                continue;
+            }
+            num_units += 1;
+            if y < grid.max_y {
+                for nx in MOVES.iter().map(|dx| x + dx) {
+                    if !grid.is_blocked(nx, y + 1) {
+                        reachable[grid.index(nx, y + 1)] = true;
+                    }
+                }
+            }
+        }
+    }
+    num_units
+}
+
+/// Simulates the grains one at a time, cell by cell, from the source at
+/// (500, 0). This is much slower than `count_resting_sand`, but it's simple
+/// and shows every step, so it's handy for visualization and for checking.
+pub fn num_units_before_flow_out(cave: &mut Cave, with_floor: bool) -> usize {
     let max_y = cave.iter().map(|&(_, y)| y).max().unwrap();
     let source = (500, 0);
     for num_units in 1.. {
@@ -53,7 +320,20 @@ fn num_units_before_flow_out(cave: &mut Cave, with_floor: bool) -> usize {
     unreachable!()
 }
 
-pub fn run(input: &str) {
+/// Saves the rocks as a JSON list of [x, y] pairs, sorted
+pub fn cave_to_json(cave: &Cave) -> String {
+    serde_json::to_string(&cave.iter().sorted().collect_vec()).unwrap()
+}
+
+pub fn cave_from_json(json: &str) -> Result<Cave, String> {
+    let rocks: Vec<(i32, i32)> = serde_json::from_str(json).map_err(|error| error.to_string())?;
+    if rocks.is_empty() {
+        return Err("the cave has no rocks".to_string());
+    }
+    Ok(rocks.into_iter().collect())
+}
+
+pub fn parse(input: &str) -> Cave {
     let segments: Vec<Vec<(i32, i32)>> = input
         .lines()
         .map(|line| {
@@ -67,11 +347,98 @@ pub fn run(input: &str) {
                 .collect_vec()
         })
         .collect_vec();
-    let mut cave = make_cave(&segments);
+    make_cave(&segments)
+}
 
-    let num_units = num_units_before_flow_out(&mut cave.clone(), false);
-    println!("{num_units}");
+pub fn part1(cave: &Cave) -> usize {
+    count_resting_sand(cave, &SandConfig::default())
+}
 
-    let num_units = num_units_before_flow_out(&mut cave, true);
-    println!("{num_units}");
+pub fn part2(cave: &Cave) -> usize {
+    count_reachable_cells(cave, &SandConfig::with_floor())
+}
+
+pub const REPL_HELP: &str = "\
+pour [floor] [x,y ...]
+                  number of grains at rest, with the floor this far below the
+                  lowest rock ('none' for the abyss, the default), and the given
+                  sources (default 500,0)
+step [floor]      same, simulating every grain step by step from 500,0 (slow)
+draw [floor] [x,y ...]
+                  draw the cave once the pouring stops, e.g. 'draw' for part 1
+                  and 'draw 2' for part 2
+frames <every> [floor] [x,y ...]
+                  draw the cave every given number of grains
+trails <every> [floor] [x,y ...]
+                  same, with the trajectory of the last grain
+save <path>       save the cave's rocks as JSON
+load <path>       replace the cave's rocks with the ones saved in a JSON file";
+
+fn parse_config(args: &[&str]) -> Result<SandConfig, String> {
+    let floor_offset = match arg(args, 0, Some("none".to_string()))?.as_str() {
+        "none" => None,
+        offset => Some(
+            offset
+                .parse()
+                .map_err(|_| format!("invalid floor offset: {offset}"))?,
+        ),
+    };
+    let mut sources = args
+        .iter()
+        .skip(1)
+        .map(|xy| {
+            xy.split(',')
+                .map(|val| val.parse::<i32>())
+                .collect_tuple()
+                .and_then(|(x, y)| Some((x.ok()?, y.ok()?)))
+                .ok_or_else(|| format!("invalid source: {xy}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if sources.is_empty() {
+        sources = SandConfig::default().sources;
+    }
+    Ok(SandConfig {
+        sources,
+        floor_offset,
+    })
+}
+
+pub fn repl_command(cave: &mut Cave, command: &str, args: &[&str]) -> CommandResult {
+    let result = match command {
+        "pour" => parse_config(args).map(|config| {
+            let mut lines = vec![format!("path memo: {}", count_resting_sand(cave, &config))];
+            if config.floor_offset.is_some() {
+                lines.push(format!(
+                    "reachable: {}",
+                    count_reachable_cells(cave, &config)
+                ));
+            }
+            lines.join("\n")
+        }),
+        "step" => parse_config(args).and_then(|config| match config.floor_offset {
+            None => Ok(num_units_before_flow_out(&mut cave.clone(), false).to_string()),
+            Some(2) => Ok(num_units_before_flow_out(&mut cave.clone(), true).to_string()),
+            Some(_) => Err("the step mode only supports a floor offset of 2".to_string()),
+        }),
+        "draw" => parse_config(args).map(|config| final_state(cave, &config)),
+        "frames" | "trails" => arg(args, 0, None).and_then(|every: usize| {
+            if every == 0 {
+                return Err("the number of grains must be positive".to_string());
+            }
+            let config = parse_config(args.get(1..).unwrap_or_default())?;
+            Ok(frames(cave, &config, every, command == "trails").join("\n\n"))
+        }),
+        "save" => arg(args, 0, None).and_then(|path: String| {
+            fs::write(&path, cave_to_json(cave))
+                .map(|_| format!("saved to {path}"))
+                .map_err(|error| error.to_string())
+        }),
+        "load" => arg(args, 0, None).and_then(|path: String| {
+            let json = fs::read_to_string(&path).map_err(|error| error.to_string())?;
+            *cave = cave_from_json(&json)?;
+            Ok(format!("loaded {} rocks", cave.len()))
+        }),
+        _ => return None,
+    };
+    Some(result)
 }
@@ -1,10 +1,10 @@
-use hashbrown::HashSet;
-
+use super::repl::{arg, CommandResult};
 use super::utils::parse_int_vecs;
 use itertools::Itertools;
+use std::fmt;
 
-#[derive(Copy, Clone)]
-struct Sensor {
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Sensor {
     sx: i64,
     sy: i64,
     bx: i64,
@@ -26,10 +26,29 @@ impl Sensor {
     fn in_range(&self, x: i64, y: i64) -> bool {
         (self.sx - x).abs() + (self.sy - y).abs() <= self.radius()
     }
+    /// The range of x covered on row y, if any
+    fn range_in_row(&self, y: i64) -> Option<(i64, i64)> {
+        let width_at_y = self.radius() - (self.sy - y).abs();
+        (width_at_y >= 0).then_some((self.sx - width_at_y, self.sx + width_at_y))
+    }
+    /// The range of y covered on column x, if any
+    fn range_in_column(&self, x: i64) -> Option<(i64, i64)> {
+        let height_at_x = self.radius() - (self.sx - x).abs();
+        (height_at_x >= 0).then_some((self.sy - height_at_x, self.sy + height_at_x))
+    }
+    pub fn position(&self) -> (i64, i64) {
+        (self.sx, self.sy)
+    }
+    pub fn closest_beacon(&self) -> (i64, i64) {
+        (self.bx, self.by)
+    }
 }
 
 /// Merges overlapping or contiguous ranges. Each range is x_min..=x_max.
 fn range_union(ranges: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    if ranges.is_empty() {
+        return vec![];
+    }
     let mut ranges = ranges.to_vec();
     ranges.sort();
     let mut union = vec![];
@@ -51,9 +70,8 @@ fn range_union(ranges: &[(i64, i64)]) -> Vec<(i64, i64)> {
 fn count_impossible_locations_in_row(sensors: &[Sensor], y: i64) -> i64 {
     let impossible_ranges = sensors
         .iter()
-        .map(|sensor| {
-            let width_at_y = sensor.radius() - (sensor.sy - y).abs();
-            let (mut min_x, mut max_x) = (sensor.sx - width_at_y, sensor.sx + width_at_y);
+        .filter_map(|sensor| {
+            let (mut min_x, mut max_x) = sensor.range_in_row(y)?;
             if sensor.by == y {
                 if sensor.bx == min_x {
                     min_x += 1
@@ -61,7 +79,7 @@ fn count_impossible_locations_in_row(sensors: &[Sensor], y: i64) -> i64 {
                     max_x -= 1
                 }
             }
-            (min_x, max_x)
+            Some((min_x, max_x))
         })
         .filter(|&(min_x, max_x)| min_x <= max_x)
         .collect_vec();
@@ -72,6 +90,83 @@ fn count_impossible_locations_in_row(sensors: &[Sensor], y: i64) -> i64 {
         .sum()
 }
 
+/// The rectangle where the distress beacon can be, bounds included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Area {
+    pub x_min: i64,
+    pub x_max: i64,
+    pub y_min: i64,
+    pub y_max: i64,
+}
+
+impl Area {
+    pub fn square(min: i64, max: i64) -> Self {
+        Self {
+            x_min: min,
+            x_max: max,
+            y_min: min,
+            y_max: max,
+        }
+    }
+}
+
+impl fmt::Display for Area {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Area {
+            x_min,
+            x_max,
+            y_min,
+            y_max,
+        } = self;
+        write!(f, "x={x_min}..={x_max}, y={y_min}..={y_max}")
+    }
+}
+
+pub const SEARCH_AREA: Area = Area {
+    x_min: 0,
+    x_max: 4_000_000,
+    y_min: 0,
+    y_max: 4_000_000,
+};
+pub const ROW: i64 = 2_000_000;
+const TUNING_MULTIPLIER: i64 = 4_000_000;
+/// Beyond this many possible positions for the distress beacon, we report how
+/// many there are rather than list them
+const MAX_CANDIDATES: usize = 1000;
+
+/// Why the distress beacon could not be located
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BeaconError {
+    EmptyArea(Area),
+    NotFound(Area),
+    Ambiguous(Vec<(i64, i64)>),
+    TooManyCandidates(Area, i64),
+}
+
+impl fmt::Display for BeaconError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BeaconError::EmptyArea(area) => write!(f, "the search area {area} is empty"),
+            BeaconError::NotFound(area) => {
+                write!(f, "every position in {area} is covered by a sensor")
+            }
+            BeaconError::Ambiguous(candidates) => write!(
+                f,
+                "the distress beacon could be at any of these {} positions: {}",
+                candidates.len(),
+                candidates
+                    .iter()
+                    .map(|(x, y)| format!("({x}, {y})"))
+                    .join(", ")
+            ),
+            BeaconError::TooManyCandidates(area, count) => write!(
+                f,
+                "the distress beacon could be at any of {count} positions in {area}"
+            ),
+        }
+    }
+}
+
 /// Iterating through all 4 million rows works fine, and it's simple enough, but
 /// it runs in 1.3s in release mode (or about 20s in debug mode). That was fine
 /// to get the star, but it's not very satisfactory, so I looked for a more
@@ -98,45 +193,707 @@ fn count_impossible_locations_in_row(sensors: &[Sensor], y: i64) -> i64 {
 /// intersection between all diagonal lines and the borders. It's just a bit
 /// boring, so I didn't bother.
 /// In the end, this algorithm now runs in 220µs. That's about 6000x faster! 😃
-fn find_tuning_frequency(sensors: &[Sensor]) -> i64 {
-    let mut sw_offsets = HashSet::new();
-    let mut nw_offsets = HashSet::new();
-    let mut ne_offsets = HashSet::new();
-    let mut se_offsets = HashSet::new();
-    sensors.iter().for_each(|sensor| {
-        let out_radius = sensor.radius() + 1;
-        sw_offsets.insert(sensor.sy + sensor.sx - out_radius);
-        nw_offsets.insert(sensor.sy - sensor.sx - out_radius);
-        ne_offsets.insert(sensor.sy + sensor.sx + out_radius);
-        se_offsets.insert(sensor.sy - sensor.sx + out_radius);
-    });
-    let sw_ne_offsets = sw_offsets.intersection(&ne_offsets).collect_vec();
-    let nw_se_offsets = nw_offsets.intersection(&se_offsets).collect_vec();
-    let (solution_x, solution_y) = sw_ne_offsets
-        .into_iter()
-        .cartesian_product(nw_se_offsets.into_iter())
-        .filter(|&(&sw_ne_offset, &nw_se_offset)| (sw_ne_offset - nw_se_offset) % 2 == 0)
-        .map(|(&sw_ne_offset, &nw_se_offset)| {
-            let x = (sw_ne_offset - nw_se_offset) / 2;
-            (x, x + nw_se_offset)
+///
+/// The search is now based on `uncovered_regions` instead, which sweeps over
+/// the sensor ranges in rotated coordinates and finds every position of the
+/// area that no sensor covers. This includes the borders and corners, as well
+/// as a beacon boxed in by ranges that only touch it along one diagonal.
+/// Returns all the candidate positions, sorted, or an error if there are more
+/// than `MAX_CANDIDATES`.
+pub fn find_beacon_candidates(
+    sensors: &[Sensor],
+    area: &Area,
+) -> Result<Vec<(i64, i64)>, BeaconError> {
+    if area.x_min > area.x_max || area.y_min > area.y_max {
+        return Err(BeaconError::EmptyArea(*area));
+    }
+    let regions = uncovered_regions(sensors, area);
+    let count = regions.iter().map(|region| region.size).sum::<i64>();
+    if count > MAX_CANDIDATES as i64 {
+        return Err(BeaconError::TooManyCandidates(*area, count));
+    }
+    let candidates = regions
+        .iter()
+        .flat_map(|region| {
+            region
+                .runs(area)
+                .flat_map(|(y, x_min, x_max)| (x_min..=x_max).map(move |x| (x, y)))
         })
-        .filter(|&(x, y)| sensors.iter().all(|sensor| !sensor.in_range(x, y)))
-        .exactly_one()
-        .unwrap();
-    let max = 4_000_000;
-    solution_x * max + solution_y
+        .sorted()
+        .collect_vec();
+    if candidates.is_empty() {
+        Err(BeaconError::NotFound(*area))
+    } else {
+        Ok(candidates)
+    }
+}
+
+/// Fails unless there's exactly one possible position for the distress beacon
+pub fn find_tuning_frequency(sensors: &[Sensor], area: &Area) -> Result<i64, BeaconError> {
+    let candidates = find_beacon_candidates(sensors, area)?;
+    match candidates[..] {
+        [(x, y)] => Ok(x * TUNING_MULTIPLIER + y),
+        _ => Err(BeaconError::Ambiguous(candidates)),
+    }
+}
+
+fn div_floor(a: i64, b: i64) -> i64 {
+    a.div_euclid(b)
 }
 
-pub fn run(input: &str) {
-    let sensors: Vec<Sensor> = parse_int_vecs(input, true)
+fn div_ceil(a: i64, b: i64) -> i64 {
+    -(-a).div_euclid(b)
+}
+
+/// Sum of max(0, first + slope * k) for k in 0..len
+fn sum_positive_linear(first: i64, slope: i64, len: i64) -> i64 {
+    if len <= 0 {
+        return 0;
+    }
+    // make the sequence non-decreasing, then skip the negative terms
+    let last = first + slope * (len - 1);
+    let (first, slope) = if slope < 0 {
+        (last, -slope)
+    } else {
+        (first, slope)
+    };
+    let skip = match (first >= 0, slope) {
+        (true, _) => 0,
+        (false, 0) => len,
+        (false, _) => div_ceil(-first, slope).min(len),
+    };
+    let (start, len) = (first + slope * skip, len - skip);
+    len * start + slope * len * (len - 1) / 2
+}
+
+/// Counts the positions (x, y) of the area such that x + y is in u_min..=u_max
+/// and y - x is in v_min..=v_max, without iterating over them. Since x + y and
+/// y - x always have the same parity, we handle each parity p separately:
+/// with x + y = 2i + p and y - x = 2j + p, we get x = i - j and y = i + j + p,
+/// so for each i, j lies in a range whose bounds are piecewise linear in i.
+fn count_positions(area: &Area, (u_min, u_max): (i64, i64), (v_min, v_max): (i64, i64)) -> i64 {
+    (0..2)
+        .map(|p| {
+            let (j_min, j_max) = (div_ceil(v_min - p, 2), div_floor(v_max - p, 2));
+            let (top, bottom) = (area.y_min - p, area.y_max - p);
+            // the number of j values for each i
+            let count = |i: i64| {
+                let high = j_max.min(i - area.x_min).min(bottom - i);
+                let low = j_min.max(i - area.x_max).max(top - i);
+                high - low + 1
+            };
+            let (i_min, i_max) = (div_ceil(u_min - p, 2), div_floor(u_max - p, 2));
+            // count is linear between the points where the bounds cross
+            let crossings = [
+                j_max + area.x_min,
+                bottom - j_max,
+                div_floor(area.x_min + bottom, 2),
+                j_min + area.x_max,
+                top - j_min,
+                div_floor(area.x_max + top, 2),
+            ];
+            crossings
+                .into_iter()
+                .flat_map(|i| [i, i + 1])
+                .filter(|&i| i_min < i && i <= i_max)
+                .chain([i_min, i_max + 1])
+                .sorted()
+                .dedup()
+                .tuple_windows()
+                .map(|(start, end)| {
+                    let slope = if end - start > 1 {
+                        count(start + 1) - count(start)
+                    } else {
+                        0
+                    };
+                    sum_positive_linear(count(start), slope, end - start)
+                })
+                .sum::<i64>()
+        })
+        .sum()
+}
+
+/// A part of the search area that no sensor covers: the positions (x, y) of
+/// the area such that x + y is in u_min..=u_max and y - x is in v_min..=v_max.
+/// In these coordinates, rotated by 45°, the sensor ranges are squares.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UncoveredRegion {
+    pub u_min: i64,
+    pub u_max: i64,
+    pub v_min: i64,
+    pub v_max: i64,
+    /// the number of positions in the region
+    pub size: i64,
+}
+
+impl UncoveredRegion {
+    /// The outline of the region, clipped to the area, as (x, y) vertices.
+    /// The region contains every position inside of it, borders included.
+    pub fn polygon(&self, area: &Area) -> Vec<(f64, f64)> {
+        let corners = [
+            (self.u_min, self.v_min),
+            (self.u_min, self.v_max),
+            (self.u_max, self.v_max),
+            (self.u_max, self.v_min),
+        ];
+        let mut polygon = corners
+            .iter()
+            .map(|&(u, v)| ((u - v) as f64 / 2.0, (u + v) as f64 / 2.0))
+            .collect_vec();
+        // Sutherland-Hodgman clipping, one border at a time
+        let borders = [
+            (true, area.x_min as f64, 1.0),
+            (true, area.x_max as f64, -1.0),
+            (false, area.y_min as f64, 1.0),
+            (false, area.y_max as f64, -1.0),
+        ];
+        for (is_vertical, bound, direction) in borders {
+            let coordinate = |p: &(f64, f64)| if is_vertical { p.0 } else { p.1 };
+            let inside = |p: &(f64, f64)| (coordinate(p) - bound) * direction >= 0.0;
+            let mut clipped = vec![];
+            for (index, current) in polygon.iter().enumerate() {
+                let previous = &polygon[(index + polygon.len() - 1) % polygon.len()];
+                if inside(current) != inside(previous) {
+                    let t = (bound - coordinate(previous))
+                        / (coordinate(current) - coordinate(previous));
+                    clipped.push((
+                        previous.0 + t * (current.0 - previous.0),
+                        previous.1 + t * (current.1 - previous.1),
+                    ));
+                }
+                if inside(current) {
+                    clipped.push(*current);
+                }
+            }
+            polygon = clipped;
+            if polygon.is_empty() {
+                break;
+            }
+        }
+        polygon.dedup();
+        if polygon.len() > 1 && polygon.first() == polygon.last() {
+            polygon.pop();
+        }
+        polygon
+    }
+
+    /// The positions of the region, as (y, x_min, x_max) runs, row by row
+    pub fn row_runs(&self, area: &Area) -> Vec<(i64, i64, i64)> {
+        self.runs(area).collect_vec()
+    }
+
+    fn runs<'a>(&'a self, area: &'a Area) -> impl Iterator<Item = (i64, i64, i64)> + 'a {
+        let y_min = div_ceil(self.u_min + self.v_min, 2).max(area.y_min);
+        let y_max = div_floor(self.u_max + self.v_max, 2).min(area.y_max);
+        (y_min..=y_max)
+            .map(|y| {
+                let x_min = (self.u_min - y).max(y - self.v_max).max(area.x_min);
+                let x_max = (self.u_max - y).min(y - self.v_min).min(area.x_max);
+                (y, x_min, x_max)
+            })
+            .filter(|&(_, x_min, x_max)| x_min <= x_max)
+    }
+}
+
+/// Finds the parts of the area that no sensor covers, with a sweep line over
+/// the rotated coordinates u = x + y and v = y - x, where each sensor range
+/// becomes a square. Between two consecutive square edges along u, the
+/// covered ranges of v don't change, so we merge them with `range_union`, and
+/// the gaps between them are uncovered. A gap that spans several slabs is
+/// extended, so each region is a rectangle in rotated coordinates (a
+/// non-rectangular hole is split into several regions).
+pub fn uncovered_regions(sensors: &[Sensor], area: &Area) -> Vec<UncoveredRegion> {
+    if area.x_min > area.x_max || area.y_min > area.y_max {
+        return vec![];
+    }
+    let (u_first, u_last) = (area.x_min + area.y_min, area.x_max + area.y_max);
+    let (v_first, v_last) = (area.y_min - area.x_max, area.y_max - area.x_min);
+    let squares = sensors
         .iter()
-        .map(|data| Sensor::new(data))
+        .map(|sensor| {
+            let (u, v, radius) = (
+                sensor.sx + sensor.sy,
+                sensor.sy - sensor.sx,
+                sensor.radius(),
+            );
+            ((u - radius, u + radius), (v - radius, v + radius))
+        })
+        .collect_vec();
+    let slab_starts = squares
+        .iter()
+        .flat_map(|&((u_min, u_max), _)| [u_min, u_max + 1])
+        .filter(|&u| u_first < u && u <= u_last)
+        .chain([u_first, u_last + 1])
+        .sorted()
+        .dedup()
         .collect_vec();
+    let mut regions: Vec<UncoveredRegion> = vec![];
+    // the regions that may be extended by the next slab
+    let mut open_regions = vec![];
+    for (&start, &end) in slab_starts.iter().tuple_windows() {
+        let covered = squares
+            .iter()
+            .filter(|&&((u_min, u_max), _)| u_min <= start && start <= u_max)
+            .map(|&(_, v_range)| v_range)
+            .collect_vec();
+        let covered = range_union(&covered);
+        let mut gaps = vec![];
+        let mut v = v_first;
+        for (v_min, v_max) in covered {
+            if v_min > v {
+                gaps.push((v, (v_min - 1).min(v_last)));
+            }
+            v = v.max(v_max + 1);
+        }
+        if v <= v_last {
+            gaps.push((v, v_last));
+        }
+        gaps.retain(|&(v_min, v_max)| v_min <= v_max);
+        open_regions = gaps
+            .into_iter()
+            .map(|(v_min, v_max)| {
+                let open_index = open_regions.iter().copied().find(|&index: &usize| {
+                    (regions[index].v_min, regions[index].v_max) == (v_min, v_max)
+                });
+                match open_index {
+                    Some(index) => {
+                        regions[index].u_max = end - 1;
+                        index
+                    }
+                    None => {
+                        regions.push(UncoveredRegion {
+                            u_min: start,
+                            u_max: end - 1,
+                            v_min,
+                            v_max,
+                            size: 0,
+                        });
+                        regions.len() - 1
+                    }
+                }
+            })
+            .collect_vec();
+    }
+    for region in &mut regions {
+        region.size = count_positions(
+            area,
+            (region.u_min, region.u_max),
+            (region.v_min, region.v_max),
+        );
+    }
+    regions.retain(|region| region.size > 0);
+    regions
+}
+
+/// The number of positions of the area covered by at least one sensor
+pub fn coverage_area(sensors: &[Sensor], area: &Area) -> i64 {
+    if area.x_min > area.x_max || area.y_min > area.y_max {
+        return 0;
+    }
+    let area_size = (area.x_max - area.x_min + 1) * (area.y_max - area.y_min + 1);
+    let uncovered: i64 = uncovered_regions(sensors, area)
+        .iter()
+        .map(|region| region.size)
+        .sum();
+    area_size - uncovered
+}
+
+/// What happens to the search area when a sensor is removed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemovalEffect {
+    pub sensor: Sensor,
+    /// the number of positions of the area that are no longer covered
+    pub lost_coverage: i64,
+    /// the regions of the area that no sensor covers once it's removed
+    pub uncovered: Vec<UncoveredRegion>,
+}
+
+/// A network of sensors, for what-if queries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SensorField {
+    sensors: Vec<Sensor>,
+}
+
+impl SensorField {
+    pub fn new(sensors: &[Sensor]) -> Self {
+        Self {
+            sensors: sensors.to_vec(),
+        }
+    }
+
+    pub fn sensors(&self) -> &[Sensor] {
+        &self.sensors
+    }
+
+    /// The indices of the sensors whose range includes the position
+    pub fn sensors_covering(&self, x: i64, y: i64) -> Vec<usize> {
+        (0..self.sensors.len())
+            .filter(|&index| self.sensors[index].in_range(x, y))
+            .collect_vec()
+    }
+
+    pub fn is_covered(&self, x: i64, y: i64) -> bool {
+        self.sensors.iter().any(|sensor| sensor.in_range(x, y))
+    }
+
+    /// The merged ranges of x covered on row y
+    pub fn row_coverage(&self, y: i64) -> Vec<(i64, i64)> {
+        let ranges = self
+            .sensors
+            .iter()
+            .filter_map(|sensor| sensor.range_in_row(y))
+            .collect_vec();
+        range_union(&ranges)
+    }
+
+    /// The merged ranges of y covered on column x
+    pub fn column_coverage(&self, x: i64) -> Vec<(i64, i64)> {
+        let ranges = self
+            .sensors
+            .iter()
+            .filter_map(|sensor| sensor.range_in_column(x))
+            .collect_vec();
+        range_union(&ranges)
+    }
+
+    /// The uncovered position closest to (x, y), in Manhattan distance. If
+    /// (x, y) is covered, the position we're looking for is next to a covered
+    /// one, so it's just outside of the range of some sensor: we look along
+    /// the 4 edges of each sensor's range, grown by one, skipping the parts
+    /// covered by other sensors. Along an edge, the distance to any point is
+    /// |t - p| + |t - q| for some p and q, so the covered parts are intervals,
+    /// and the closest point is easy to find.
+    pub fn nearest_uncovered(&self, x: i64, y: i64) -> (i64, i64) {
+        if !self.is_covered(x, y) {
+            return (x, y);
+        }
+        // distance from (x0 + dx * t, y0 + dy * t) to (px, py) is |t - p| + |t - q|
+        let along = |(x0, y0, dx, dy): (i64, i64, i64, i64), (px, py): (i64, i64)| {
+            (dx * (px - x0), dy * (py - y0))
+        };
+        let mut best: Option<(i64, (i64, i64))> = None;
+        for sensor in &self.sensors {
+            let out_radius = sensor.radius() + 1;
+            let edges = [
+                (sensor.sx + out_radius, sensor.sy, -1, 1),
+                (sensor.sx, sensor.sy + out_radius, -1, -1),
+                (sensor.sx - out_radius, sensor.sy, 1, -1),
+                (sensor.sx, sensor.sy - out_radius, 1, 1),
+            ];
+            for edge in edges {
+                let covered = self
+                    .sensors
+                    .iter()
+                    .filter_map(|other| {
+                        let (p, q) = along(edge, (other.sx, other.sy));
+                        let slack = other.radius() - (p - q).abs();
+                        (slack >= 0).then(|| {
+                            (
+                                div_ceil(p + q - other.radius(), 2),
+                                div_floor(p + q + other.radius(), 2),
+                            )
+                        })
+                    })
+                    .collect_vec();
+                let (p, q) = along(edge, (x, y));
+                let mut start = 0;
+                let mut uncovered = vec![];
+                for (t_min, t_max) in range_union(&covered) {
+                    if t_min > start {
+                        uncovered.push((start, (t_min - 1).min(out_radius - 1)));
+                    }
+                    start = start.max(t_max + 1);
+                }
+                // each edge excludes its last point, which starts the next edge
+                if start < out_radius {
+                    uncovered.push((start, out_radius - 1));
+                }
+                for (t_min, t_max) in uncovered.into_iter().filter(|(a, b)| a <= b) {
+                    let t = [t_min, t_max, p.clamp(t_min, t_max), q.clamp(t_min, t_max)]
+                        .into_iter()
+                        .min_by_key(|&t| (t - p).abs() + (t - q).abs())
+                        .unwrap();
+                    let distance = (t - p).abs() + (t - q).abs();
+                    let position = (edge.0 + edge.2 * t, edge.1 + edge.3 * t);
+                    if best.is_none_or(|best| (distance, position) < best) {
+                        best = Some((distance, position));
+                    }
+                }
+            }
+        }
+        best.expect("a covered position must have a sensor").1
+    }
+
+    /// The same field, without the sensor at the given index
+    pub fn without_sensor(&self, index: usize) -> Self {
+        let mut sensors = self.sensors.clone();
+        sensors.remove(index);
+        Self { sensors }
+    }
+
+    pub fn removal_effect(&self, index: usize, area: &Area) -> RemovalEffect {
+        let remaining = self.without_sensor(index);
+        RemovalEffect {
+            sensor: self.sensors[index],
+            lost_coverage: coverage_area(&self.sensors, area)
+                - coverage_area(&remaining.sensors, area),
+            uncovered: uncovered_regions(&remaining.sensors, area),
+        }
+    }
+}
+
+pub fn parse(input: &str) -> Vec<Sensor> {
+    parse_int_vecs(input, true)
+        .iter()
+        .map(|data| Sensor::new(data))
+        .collect_vec()
+}
+
+pub fn part1(sensors: &[Sensor]) -> i64 {
+    count_impossible_locations_in_row(sensors, ROW)
+}
 
-    let y = 2_000_000;
-    let num_impossible = count_impossible_locations_in_row(&sensors, y);
-    println!("{num_impossible}");
+pub fn part2(sensors: &[Sensor]) -> i64 {
+    find_tuning_frequency(sensors, &SEARCH_AREA).unwrap_or_else(|error| panic!("{error}"))
+}
+
+pub const REPL_HELP: &str = "\
+row [y]           number of positions where a beacon cannot be on the given row
+                  (default 2000000)
+candidates [min] [max]
+                  possible positions of the distress beacon, in the square
+                  search area (default 0 4000000)
+tuning [min] [max]
+                  tuning frequency of the distress beacon
+coverage [min] [max]
+                  number of positions covered and not covered by the sensors
+uncovered [min] [max]
+                  regions not covered by any sensor, as polygons
+runs [min] [max]  same, as row runs
+sensors           list the sensors, with their closest beacon and radius
+covering <x,y>    the sensors covering the given position
+rowcov <y>        the ranges of x covered on the given row
+colcov <x>        the ranges of y covered on the given column
+nearest <x,y>     the uncovered position closest to the given one
+remove <index> [min] [max]
+                  what happens to the search area without the given sensor";
 
-    let frequency = find_tuning_frequency(&sensors);
-    println!("{frequency}");
+fn parse_position(args: &[&str]) -> Result<(i64, i64), String> {
+    let position: String = arg(args, 0, None)?;
+    position
+        .split(',')
+        .map(|value| value.trim().parse().ok())
+        .collect_tuple()
+        .and_then(|(x, y)| Some((x?, y?)))
+        .ok_or_else(|| format!("invalid position: {position}, expected x,y"))
+}
+
+fn format_ranges(ranges: &[(i64, i64)]) -> String {
+    ranges
+        .iter()
+        .map(|(min, max)| format!("{min}..={max}"))
+        .join(", ")
+}
+
+pub fn repl_command(sensors: &mut [Sensor], command: &str, args: &[&str]) -> CommandResult {
+    let area = || -> Result<Area, String> {
+        Ok(Area::square(
+            arg(args, 0, Some(SEARCH_AREA.x_min))?,
+            arg(args, 1, Some(SEARCH_AREA.x_max))?,
+        ))
+    };
+    let field = SensorField::new(sensors);
+    let result = match command {
+        "row" => arg(args, 0, Some(ROW))
+            .map(|y| count_impossible_locations_in_row(sensors, y).to_string()),
+        "candidates" => area().and_then(|area| {
+            find_beacon_candidates(sensors, &area)
+                .map(|candidates| {
+                    candidates
+                        .iter()
+                        .map(|(x, y)| format!("{x},{y}"))
+                        .join("\n")
+                })
+                .map_err(|error| error.to_string())
+        }),
+        "tuning" => area().and_then(|area| {
+            find_tuning_frequency(sensors, &area)
+                .map(|frequency| frequency.to_string())
+                .map_err(|error| error.to_string())
+        }),
+        "coverage" => area().map(|area| {
+            let covered = coverage_area(sensors, &area);
+            let total = (area.x_max - area.x_min + 1).max(0) * (area.y_max - area.y_min + 1).max(0);
+            format!("covered: {covered}\nnot covered: {}", total - covered)
+        }),
+        "uncovered" => area().map(|area| {
+            uncovered_regions(sensors, &area)
+                .iter()
+                .map(|region| {
+                    let polygon = region
+                        .polygon(&area)
+                        .iter()
+                        .map(|(x, y)| format!("({x}, {y})"))
+                        .join(" ");
+                    format!("{} positions: {polygon}", region.size)
+                })
+                .join("\n")
+        }),
+        "runs" => area().map(|area| {
+            uncovered_regions(sensors, &area)
+                .iter()
+                .flat_map(|region| region.row_runs(&area))
+                .sorted()
+                .map(|(y, x_min, x_max)| format!("y={y}: x={x_min}..={x_max}"))
+                .join("\n")
+        }),
+        "sensors" => Ok(field
+            .sensors()
+            .iter()
+            .enumerate()
+            .map(|(index, sensor)| {
+                let (sx, sy) = sensor.position();
+                let (bx, by) = sensor.closest_beacon();
+                format!(
+                    "{index:>3}. sensor at {sx},{sy}, beacon at {bx},{by}, radius {}",
+                    sensor.radius()
+                )
+            })
+            .join("\n")),
+        "covering" => parse_position(args).map(|(x, y)| {
+            let indices = field.sensors_covering(x, y);
+            if indices.is_empty() {
+                "not covered".to_string()
+            } else {
+                indices.iter().join(", ")
+            }
+        }),
+        "rowcov" => arg(args, 0, None).map(|y| format_ranges(&field.row_coverage(y))),
+        "colcov" => arg(args, 0, None).map(|x| format_ranges(&field.column_coverage(x))),
+        "nearest" => parse_position(args).map(|(x, y)| {
+            let (nx, ny) = field.nearest_uncovered(x, y);
+            format!("{nx},{ny} (distance {})", (nx - x).abs() + (ny - y).abs())
+        }),
+        "remove" => arg(args, 0, None).and_then(|index: usize| {
+            if index >= field.sensors().len() {
+                return Err(format!("unknown sensor {index}"));
+            }
+            let area = Area::square(
+                arg(args, 1, Some(SEARCH_AREA.x_min))?,
+                arg(args, 2, Some(SEARCH_AREA.x_max))?,
+            );
+            let effect = field.removal_effect(index, &area);
+            let num_uncovered: i64 = effect.uncovered.iter().map(|region| region.size).sum();
+            Ok(format!(
+                "lost coverage: {}\nuncovered positions: {num_uncovered} in {} regions",
+                effect.lost_coverage,
+                effect.uncovered.len()
+            ))
+        }),
+        _ => return None,
+    };
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+Sensor at x=9, y=16: closest beacon is at x=10, y=16
+Sensor at x=13, y=2: closest beacon is at x=15, y=3
+Sensor at x=12, y=14: closest beacon is at x=10, y=16
+Sensor at x=10, y=20: closest beacon is at x=10, y=16
+Sensor at x=14, y=17: closest beacon is at x=10, y=16
+Sensor at x=8, y=7: closest beacon is at x=2, y=10
+Sensor at x=2, y=0: closest beacon is at x=2, y=10
+Sensor at x=0, y=11: closest beacon is at x=2, y=10
+Sensor at x=20, y=14: closest beacon is at x=25, y=17
+Sensor at x=17, y=20: closest beacon is at x=21, y=22
+Sensor at x=16, y=7: closest beacon is at x=15, y=3
+Sensor at x=14, y=3: closest beacon is at x=15, y=3
+Sensor at x=20, y=1: closest beacon is at x=15, y=3";
+
+    /// The beacon is boxed in by ranges that don't abut on all four sides
+    const BOXED_IN: &str = "\
+Sensor at x=9, y=2: closest beacon is at x=5, y=4
+Sensor at x=4, y=-3: closest beacon is at x=10, y=-6
+Sensor at x=4, y=9: closest beacon is at x=6, y=8
+Sensor at x=8, y=11: closest beacon is at x=6, y=15
+Sensor at x=-3, y=9: closest beacon is at x=3, y=14
+Sensor at x=13, y=1: closest beacon is at x=15, y=7";
+
+    #[test]
+    fn example_tuning_frequency() {
+        let sensors = parse(EXAMPLE);
+        assert_eq!(count_impossible_locations_in_row(&sensors, 10), 26);
+        assert_eq!(
+            find_tuning_frequency(&sensors, &Area::square(0, 20)),
+            Ok(56000011)
+        );
+    }
+
+    #[test]
+    fn all_candidates_or_too_many() {
+        let sensors = parse(EXAMPLE);
+        let candidates = find_beacon_candidates(&sensors, &Area::square(0, 30)).unwrap();
+        assert_eq!(candidates.len(), 352);
+        assert!(candidates.contains(&(14, 11)));
+        let area = Area::square(0, 40);
+        assert_eq!(
+            find_beacon_candidates(&sensors, &area),
+            Err(BeaconError::TooManyCandidates(area, 1072))
+        );
+    }
+
+    #[test]
+    fn beacon_boxed_in_by_non_abutting_ranges() {
+        let sensors = parse(BOXED_IN);
+        let area = Area::square(0, 10);
+        assert_eq!(find_beacon_candidates(&sensors, &area), Ok(vec![(6, 6)]));
+        assert_eq!(find_tuning_frequency(&sensors, &area), Ok(24000006));
+        assert_eq!(coverage_area(&sensors, &area), 120);
+    }
+
+    #[test]
+    fn sensor_field_coverage() {
+        let field = SensorField::new(&parse(EXAMPLE));
+        assert_eq!(field.sensors_covering(2, 10), vec![6, 7, 8]);
+        assert!(field.sensors_covering(14, 11).is_empty());
+        assert!(!field.is_covered(14, 11));
+        assert_eq!(field.row_coverage(10), vec![(-2, 24)]);
+        assert_eq!(field.row_coverage(11), vec![(-3, 13), (15, 25)]);
+        assert_eq!(field.column_coverage(14), vec![(0, 10), (12, 23)]);
+    }
+
+    #[test]
+    fn sensor_field_nearest_uncovered() {
+        let field = SensorField::new(&parse(EXAMPLE));
+        assert_eq!(field.nearest_uncovered(14, 11), (14, 11));
+        for ((x, y), distance) in [((8, 7), 10), ((2, 10), 5)] {
+            let (nx, ny) = field.nearest_uncovered(x, y);
+            assert!(!field.is_covered(nx, ny));
+            assert_eq!((nx - x).abs() + (ny - y).abs(), distance);
+        }
+    }
+
+    #[test]
+    fn sensor_field_removal_effect() {
+        let field = SensorField::new(&parse(EXAMPLE));
+        let area = Area::square(0, 20);
+        let effect = field.removal_effect(6, &area);
+        assert_eq!(effect.sensor, field.sensors()[6]);
+        assert_eq!(effect.lost_coverage, 66);
+        assert_eq!(
+            effect
+                .uncovered
+                .iter()
+                .map(|region| region.size)
+                .sum::<i64>(),
+            67
+        );
+        assert_eq!(field.without_sensor(6).sensors().len(), 13);
+        assert_eq!(field.removal_effect(0, &area).lost_coverage, 54);
+    }
 }
@@ -1,26 +1,42 @@
 use hashbrown::HashMap;
 use itertools::Itertools;
 use regex::Regex;
+
+use super::repl::{arg, CommandResult};
 use std::cmp::{max, min};
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
+use std::time::Instant;
+use std::{fmt, fs};
 
 #[derive(Debug, Clone)]
 struct Valve {
+    name: String,
     index: usize,
     flow_rate: i32,
+    tunnels: Vec<usize>,
     distance: Vec<i32>, // shortest distance to each valve
     is_open: bool,
 }
 
+/// The distance between two valves when there's no path from one to the
+/// other: far more than any number of minutes, so that the solvers never
+/// schedule such a move, yet small enough that adding two of them can't
+/// overflow.
+const UNREACHABLE: i32 = i32::MAX / 2;
+
 /// Floyd–Warshall algorithm to find all pair-wise distances between valves
 /// See https://en.wikipedia.org/wiki/Floyd%E2%80%93Warshall_algorithm
 fn find_all_distances(valve_tunnels: &[Vec<usize>]) -> Vec<Vec<i32>> {
     let len = valve_tunnels.len();
-    let mut distance = vec![vec![len as i32; len]; len]; // len × len matrix
+    let mut distance = vec![vec![UNREACHABLE; len]; len]; // len × len matrix
     for (from_index, to_indices) in valve_tunnels.iter().enumerate() {
-        distance[from_index][from_index] = 0;
         for &to_index in to_indices {
             distance[from_index][to_index] = 1;
         }
+        // after the tunnels, in case a tunnel loops back to its own valve
+        distance[from_index][from_index] = 0;
     }
     for ((k, i), j) in (0..len).cartesian_product(0..len).cartesian_product(0..len) {
         distance[i][j] = min(distance[i][j], distance[i][k] + distance[k][j]);
@@ -28,6 +44,14 @@ fn find_all_distances(valve_tunnels: &[Vec<usize>]) -> Vec<Vec<i32>> {
     distance
 }
 
+/// Branch and bound search: the actor with the most time left makes the next
+/// decision, trying the closed valves by decreasing pressure released, and
+/// skipping the branches that can't beat the best plan found so far.
+/// With several actors, an actor may also retire for good, leaving the
+/// remaining valves to the others. Otherwise it would never stop while it has
+/// a valve within reach, and with three or more actors that can miss the
+/// optimum, where one actor should leave a valve to another one who gets
+/// there later but has nothing better to do.
 fn max_pressure_release(
     valves: &mut [Valve],
     pressure: i32,
@@ -81,7 +105,7 @@ fn max_pressure_release(
         return pressure;
     }
 
-    prioritized_valves
+    let best_opening = prioritized_valves
         .into_iter()
         .map(|(releasable_pressure, distance, to_index)| {
             valves[to_index].is_open = true;
@@ -104,41 +128,111 @@ fn max_pressure_release(
             branch_pressure
         })
         .max()
-        .unwrap_or(pressure)
+        .unwrap_or(pressure);
+
+    // with several actors, this actor may also stop here, leaving the
+    // remaining valves to the other actors
+    let others_are_active = next_decision_times
+        .iter()
+        .enumerate()
+        .any(|(index, &time)| index != actor_index && time > 0);
+    if time_left <= 0 || !others_are_active {
+        return best_opening;
+    }
+    next_decision_times[actor_index] = 0;
+    let retired_pressure = max_pressure_release(
+        valves,
+        pressure,
+        max(best_so_far, best_opening),
+        locations,
+        next_decision_times,
+    );
+    next_decision_times[actor_index] = time_left;
+    max(best_opening, retired_pressure)
 }
 
-pub fn run(input: &str) {
-    // parse data
+pub struct Volcano {
+    valves: Vec<Valve>,
+    start_index: usize,
+}
+
+/// A problem in the puzzle input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VolcanoError {
+    InvalidLine(String),
+    DuplicateValve(String),
+    UndefinedValve { valve: String, tunnel: String },
+    MissingStart,
+    UnreachableValve(String),
+}
+
+impl fmt::Display for VolcanoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VolcanoError::InvalidLine(line) => write!(f, "invalid line: {line:?}"),
+            VolcanoError::DuplicateValve(name) => write!(f, "valve {name} is defined twice"),
+            VolcanoError::UndefinedValve { valve, tunnel } => {
+                write!(f, "valve {valve} has a tunnel to undefined valve {tunnel}")
+            }
+            VolcanoError::MissingStart => write!(f, "there is no valve AA to start from"),
+            VolcanoError::UnreachableValve(name) => write!(
+                f,
+                "valve {name} has a positive flow rate but can't be reached from AA"
+            ),
+        }
+    }
+}
+
+/// Parses the valves, checking that AA exists, that every tunnel leads to a
+/// defined valve, and that every valve with a positive flow rate can be
+/// reached from AA
+pub fn try_parse(input: &str) -> Result<Volcano, VolcanoError> {
     let regex =
-        Regex::new(r"Valve ([A-Z]+) has flow rate=(\d+); tunnels? leads? to valves? ([A-Z ,]+)")
+        Regex::new(r"^Valve ([A-Z]+) has flow rate=(\d+); tunnels? leads? to valves? ([A-Z ,]+)$")
             .unwrap();
     let mut name_to_index: HashMap<String, usize> = HashMap::new();
     let mut tunnels = vec![];
-    let mut valves = regex
-        .captures_iter(input)
+    let mut valves = input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
         .enumerate()
-        .map(|(index, cap)| {
-            name_to_index.insert(cap[1].to_string(), index);
+        .map(|(index, line)| {
+            let invalid = || VolcanoError::InvalidLine(line.to_string());
+            let cap = regex.captures(line).ok_or_else(invalid)?;
+            if name_to_index.insert(cap[1].to_string(), index).is_some() {
+                return Err(VolcanoError::DuplicateValve(cap[1].to_string()));
+            }
             tunnels.push(cap[3].to_string());
-            Valve {
+            Ok(Valve {
+                name: cap[1].to_string(),
                 index,
-                flow_rate: cap[2].parse::<i32>().unwrap(),
+                flow_rate: cap[2].parse::<i32>().map_err(|_| invalid())?,
+                tunnels: vec![],
                 distance: vec![],
                 is_open: false,
-            }
+            })
         })
-        .collect_vec();
+        .collect::<Result<Vec<_>, _>>()?;
 
     // convert valve names to indices and find all pair-wise distances
-    let valve_tunnels = tunnels
-        .into_iter()
-        .map(|tunnels| {
-            tunnels
-                .split(", ")
-                .into_iter()
-                .map(|name| *name_to_index.get(name).unwrap())
-                .collect_vec()
-        })
+    for (valve, tunnels) in valves.iter_mut().zip(tunnels) {
+        valve.tunnels = tunnels
+            .split(", ")
+            .map(|name| {
+                name_to_index
+                    .get(name)
+                    .copied()
+                    .ok_or_else(|| VolcanoError::UndefinedValve {
+                        valve: valve.name.clone(),
+                        tunnel: name.to_string(),
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+    }
+    let valve_tunnels = valves
+        .iter()
+        .map(|valve| valve.tunnels.clone())
         .collect_vec();
     find_all_distances(&valve_tunnels)
         .into_iter()
@@ -146,16 +240,717 @@ pub fn run(input: &str) {
         .for_each(|(index, distance)| {
             valves[index].distance = distance;
         });
-    let &start_index = name_to_index.get("AA").unwrap();
-    let pressure = max_pressure_release(&mut valves, 0, 30, &mut vec![start_index], &mut vec![30]);
-    println!("{pressure}");
-
-    let pressure = max_pressure_release(
-        &mut valves,
-        0,
-        26,
-        &mut vec![start_index, start_index],
-        &mut vec![26, 26],
+    let &start_index = name_to_index.get("AA").ok_or(VolcanoError::MissingStart)?;
+    if let Some(valve) = valves.iter().find(|valve| {
+        valve.flow_rate > 0 && valves[start_index].distance[valve.index] == UNREACHABLE
+    }) {
+        return Err(VolcanoError::UnreachableValve(valve.name.clone()));
+    }
+    Ok(Volcano {
+        valves,
+        start_index,
+    })
+}
+
+pub fn parse(input: &str) -> Volcano {
+    try_parse(input).unwrap_or_else(|error| panic!("{error}"))
+}
+
+/// A node in the tree of paths explored by `best_pressure_per_subset`: a valve
+/// that was opened with `time_left` minutes left, after the parent node's.
+struct OpenedValve {
+    valve_index: usize,
+    time_left: i32,
+    parent: Option<usize>,
+}
+
+/// For each subset of the useful valves, the max pressure a single actor can
+/// release by opening only valves from that subset, and the last valve opened
+/// on the best path, if any.
+struct SubsetPressures {
+    pressure: Vec<i32>,
+    last_opened: Vec<Option<usize>>,
+    nodes: Vec<OpenedValve>,
+}
+
+impl SubsetPressures {
+    /// Returns the valves opened on the best path for the given subset, in
+    /// order, along with the time left after opening each of them
+    fn path(&self, subset: usize) -> Vec<(usize, i32)> {
+        let mut path = vec![];
+        let mut node = self.last_opened[subset];
+        while let Some(index) = node {
+            let opened = &self.nodes[index];
+            path.push((opened.valve_index, opened.time_left));
+            node = opened.parent;
+        }
+        path.reverse();
+        path
+    }
+}
+
+/// Every path is explored, but a state is pruned if the same valves were
+/// already opened, ending at the same place at the same time, with at least as
+/// much pressure.
+fn best_pressure_per_subset(volcano: &Volcano, useful: &[usize], minutes: i32) -> SubsetPressures {
+    let mut pressures = SubsetPressures {
+        pressure: vec![0; 1 << useful.len()],
+        last_opened: vec![None; 1 << useful.len()],
+        nodes: vec![],
+    };
+    let mut seen: HashMap<(u32, usize, i32), i32> = HashMap::new();
+    // (opened valves bitmask, last node or None, pressure)
+    let mut stack: Vec<(u32, Option<usize>, i32)> = vec![(0, None, 0)];
+    while let Some((opened, node, pressure)) = stack.pop() {
+        if pressure > pressures.pressure[opened as usize] {
+            pressures.pressure[opened as usize] = pressure;
+            pressures.last_opened[opened as usize] = node;
+        }
+        let (location, time_left) = node.map_or((volcano.start_index, minutes), |node| {
+            (
+                pressures.nodes[node].valve_index,
+                pressures.nodes[node].time_left,
+            )
+        });
+        for (next, &valve_index) in useful.iter().enumerate() {
+            let valve = &volcano.valves[valve_index];
+            let time_left = time_left - volcano.valves[location].distance[valve_index] - 1;
+            if opened & (1 << next) != 0 || time_left <= 0 {
+                continue;
+            }
+            let opened = opened | (1 << next);
+            let pressure = pressure + time_left * valve.flow_rate;
+            let previous = seen.entry((opened, next, time_left)).or_insert(-1);
+            if *previous < pressure {
+                *previous = pressure;
+                pressures.nodes.push(OpenedValve {
+                    valve_index,
+                    time_left,
+                    parent: node,
+                });
+                stack.push((opened, Some(pressures.nodes.len() - 1), pressure));
+            }
+        }
+    }
+    // a subset's best is also achievable with any superset
+    for bit in 0..useful.len() {
+        for subset in 0..pressures.pressure.len() {
+            let smaller = subset ^ (1 << bit);
+            if subset & (1 << bit) != 0 && pressures.pressure[smaller] > pressures.pressure[subset]
+            {
+                pressures.pressure[subset] = pressures.pressure[smaller];
+                pressures.last_opened[subset] = pressures.last_opened[smaller];
+            }
+        }
+    }
+    pressures
+}
+
+/// The most valves with a positive flow rate that `max_pressure_dp` accepts.
+/// Its 3^n combine step is about 3.5 billion steps per extra actor for 20
+/// valves (several seconds), and it triples with every extra valve. The puzzle
+/// inputs have 15 of them.
+const MAX_USEFUL_VALVES: usize = 20;
+
+/// Exact solver based on dynamic programming over the subsets of useful
+/// valves: since actors never need to open the same valve, the best plan for N
+/// actors splits the valves between the N-1 first actors and the last one.
+/// This costs O(3^n) per extra actor, where n is the number of useful valves,
+/// on top of the O(2^n) memory for the best pressure of each subset.
+/// Returns the max pressure, and for each actor the valves it opens, along
+/// with the time left after opening each of them.
+fn max_pressure_dp(
+    volcano: &Volcano,
+    minutes: i32,
+    num_actors: usize,
+) -> (i32, Vec<Vec<(usize, i32)>>) {
+    let useful = volcano
+        .valves
+        .iter()
+        .filter(|valve| valve.flow_rate > 0)
+        .map(|valve| valve.index)
+        .collect_vec();
+    assert!(
+        useful.len() <= MAX_USEFUL_VALVES,
+        "too many valves with a positive flow rate: {}, at most {MAX_USEFUL_VALVES} are supported",
+        useful.len()
     );
-    println!("{pressure}");
+    let best = best_pressure_per_subset(volcano, &useful, minutes);
+    let mut combined = best.pressure.clone();
+    // for each extra actor, the best subset to leave to the previous actors
+    let mut splits = vec![];
+    for _ in 1..num_actors {
+        let (pressures, parts): (Vec<_>, Vec<_>) = (0..combined.len())
+            .map(|subset| {
+                // enumerate all the sub-subsets of subset, including 0
+                let mut result = (best.pressure[subset], 0);
+                let mut part = subset;
+                while part > 0 {
+                    part = (part - 1) & subset;
+                    let pressure = combined[part] + best.pressure[subset ^ part];
+                    result = max(result, (pressure, part));
+                }
+                result
+            })
+            .unzip();
+        combined = pressures;
+        splits.push(parts);
+    }
+    let mut subset = combined.len() - 1;
+    let mut plans = vec![];
+    for parts in splits.iter().rev() {
+        plans.push(best.path(subset ^ parts[subset]));
+        subset = parts[subset];
+    }
+    plans.push(best.path(subset));
+    plans.reverse();
+    (combined[combined.len() - 1], plans)
+}
+
+/// The algorithms that can compute the max pressure release
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Solver {
+    /// Branch and bound search, guided by a greedy heuristic
+    BranchAndBound,
+    /// Dynamic programming over the subsets of valves
+    Bitmask,
+}
+
+impl FromStr for Solver {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "bnb" => Ok(Solver::BranchAndBound),
+            "dp" => Ok(Solver::Bitmask),
+            _ => Err(format!("unknown solver {name}, expected bnb or dp")),
+        }
+    }
+}
+
+pub fn max_pressure(volcano: &Volcano, minutes: i32, num_actors: usize, solver: Solver) -> i32 {
+    let start_index = volcano.start_index;
+    if num_actors == 0 {
+        return 0;
+    }
+    match solver {
+        Solver::BranchAndBound => max_pressure_release(
+            &mut volcano.valves.clone(),
+            0,
+            minutes,
+            &mut vec![start_index; num_actors],
+            &mut vec![minutes; num_actors],
+        ),
+        Solver::Bitmask => max_pressure_dp(volcano, minutes, num_actors).0,
+    }
+}
+
+/// A valve opened by an actor, who first walks through the tunnels along the
+/// given path (one valve per minute), then opens the valve during `minute`.
+#[derive(Debug, Clone)]
+pub struct Opening {
+    pub minute: i32,
+    pub valve: String,
+    pub flow_rate: i32,
+    pub path: Vec<String>,
+}
+
+/// An optimal plan: the valves that each actor opens, in order. The first
+/// actor is you, and the others are elephants.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    pub minutes: i32,
+    pub pressure: i32,
+    pub actors: Vec<Vec<Opening>>,
+}
+
+fn actor_name(actor_index: usize) -> String {
+    match actor_index {
+        0 => "You".to_string(),
+        1 => "The elephant".to_string(),
+        _ => format!("Elephant #{actor_index}"),
+    }
+}
+
+/// Returns the valves along a shortest path, excluding the start valve, or
+/// None if there's no path
+fn shortest_path(volcano: &Volcano, from_index: usize, to_index: usize) -> Option<Vec<usize>> {
+    let valves = &volcano.valves;
+    if valves[from_index].distance[to_index] == UNREACHABLE {
+        return None;
+    }
+    let mut path = vec![];
+    let mut index = from_index;
+    while index != to_index {
+        index = (0..valves.len()).find(|&next| {
+            valves[index].distance[next] == 1
+                && valves[next].distance[to_index] == valves[index].distance[to_index] - 1
+        })?;
+        path.push(index);
+    }
+    Some(path)
+}
+
+/// Finds an optimal plan, using the bitmask DP solver
+pub fn optimal_plan(volcano: &Volcano, minutes: i32, num_actors: usize) -> Plan {
+    if num_actors == 0 {
+        return Plan {
+            minutes,
+            pressure: 0,
+            actors: vec![],
+        };
+    }
+    let (pressure, actor_paths) = max_pressure_dp(volcano, minutes, num_actors);
+    let actors = actor_paths
+        .into_iter()
+        .map(|path| {
+            let mut location = volcano.start_index;
+            path.into_iter()
+                .map(|(valve_index, time_left)| {
+                    // the solvers never schedule a move to an unreachable valve
+                    let tunnels = shortest_path(volcano, location, valve_index)
+                        .expect("the next valve can't be reached");
+                    location = valve_index;
+                    let valve = &volcano.valves[valve_index];
+                    Opening {
+                        minute: minutes - time_left,
+                        valve: valve.name.clone(),
+                        flow_rate: valve.flow_rate,
+                        path: tunnels
+                            .into_iter()
+                            .map(|index| volcano.valves[index].name.clone())
+                            .collect(),
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    Plan {
+        minutes,
+        pressure,
+        actors,
+    }
+}
+
+impl Plan {
+    /// Describes what happens during each minute, like in the puzzle statement
+    pub fn timeline(&self) -> String {
+        // what each actor does during each minute
+        let mut actions: Vec<Vec<String>> = vec![vec![]; self.minutes as usize + 1];
+        for (actor_index, openings) in self.actors.iter().enumerate() {
+            let actor = actor_name(actor_index);
+            // "You move", but "The elephant moves"
+            let (move_verb, open_verb) = match actor_index {
+                0 => ("move", "open"),
+                _ => ("moves", "opens"),
+            };
+            for opening in openings {
+                let start = opening.minute - opening.path.len() as i32;
+                for (step, valve) in opening.path.iter().enumerate() {
+                    let minute = start + step as i32;
+                    actions[minute as usize].push(format!("{actor} {move_verb} to valve {valve}."));
+                }
+                actions[opening.minute as usize]
+                    .push(format!("{actor} {open_verb} valve {}.", opening.valve));
+            }
+        }
+        let openings = self.actors.iter().flatten().collect_vec();
+        (1..=self.minutes)
+            .map(|minute| {
+                let open = openings
+                    .iter()
+                    .filter(|opening| opening.minute < minute)
+                    .sorted_by_key(|opening| &opening.valve)
+                    .collect_vec();
+                let flow = open.iter().map(|opening| opening.flow_rate).sum::<i32>();
+                let names = open.iter().map(|opening| &opening.valve).collect_vec();
+                let status = match names.len() {
+                    0 => "No valves are open.".to_string(),
+                    1 => format!("Valve {} is open, releasing {flow} pressure.", names[0]),
+                    2 => format!(
+                        "Valves {} and {} are open, releasing {flow} pressure.",
+                        names[0], names[1]
+                    ),
+                    len => format!(
+                        "Valves {}, and {} are open, releasing {flow} pressure.",
+                        names[..len - 1].iter().join(", "),
+                        names[len - 1]
+                    ),
+                };
+                [format!("== Minute {minute} =="), status]
+                    .into_iter()
+                    .chain(actions[minute as usize].iter().cloned())
+                    .join("\n")
+            })
+            .join("\n\n")
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Max pressure released in {} minutes: {}",
+            self.minutes, self.pressure
+        )?;
+        for (actor_index, openings) in self.actors.iter().enumerate() {
+            write!(f, "\n{}:", actor_name(actor_index))?;
+            for opening in openings {
+                write!(
+                    f,
+                    "\n  minute {:>2}: open {} (flow rate {}, releasing {}) via {}",
+                    opening.minute,
+                    opening.valve,
+                    opening.flow_rate,
+                    opening.flow_rate * (self.minutes - opening.minute),
+                    opening.path.join(" -> ")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Basic facts about the valve network, ignoring the direction of tunnels
+/// when looking for connected components
+#[derive(Debug, Clone)]
+pub struct NetworkStats {
+    pub num_valves: usize,
+    pub num_useful_valves: usize,
+    pub num_tunnels: usize,
+    pub components: Vec<Vec<String>>,
+    /// the longest shortest path between two connected valves
+    pub diameter: i32,
+}
+
+pub fn network_stats(volcano: &Volcano) -> NetworkStats {
+    let valves = &volcano.valves;
+    let mut neighbors = vec![vec![]; valves.len()];
+    for valve in valves {
+        for &to_index in &valve.tunnels {
+            neighbors[valve.index].push(to_index);
+            neighbors[to_index].push(valve.index);
+        }
+    }
+    let mut component_of = vec![None; valves.len()];
+    let mut components = vec![];
+    for start in 0..valves.len() {
+        if component_of[start].is_some() {
+            continue;
+        }
+        component_of[start] = Some(components.len());
+        let mut component = vec![start];
+        let mut queue = VecDeque::from([start]);
+        while let Some(index) = queue.pop_front() {
+            for &next in &neighbors[index] {
+                if component_of[next].is_none() {
+                    component_of[next] = Some(components.len());
+                    component.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+        components.push(
+            component
+                .into_iter()
+                .map(|index| valves[index].name.clone())
+                .sorted()
+                .collect(),
+        );
+    }
+    let diameter = valves
+        .iter()
+        .flat_map(|valve| valve.distance.iter())
+        .filter(|&&distance| distance < UNREACHABLE)
+        .max()
+        .copied()
+        .unwrap_or(0);
+    NetworkStats {
+        num_valves: valves.len(),
+        num_useful_valves: valves.iter().filter(|valve| valve.flow_rate > 0).count(),
+        num_tunnels: valves.iter().map(|valve| valve.tunnels.len()).sum(),
+        components,
+        diameter,
+    }
+}
+
+impl fmt::Display for NetworkStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "valves: {}", self.num_valves)?;
+        writeln!(
+            f,
+            "useful valves (positive flow rate): {}",
+            self.num_useful_valves
+        )?;
+        writeln!(f, "tunnels (one way): {}", self.num_tunnels)?;
+        writeln!(f, "connected components: {}", self.components.len())?;
+        if self.components.len() > 1 {
+            for component in &self.components {
+                writeln!(f, "  {}", component.join(", "))?;
+            }
+        }
+        write!(f, "diameter: {}", self.diameter)
+    }
+}
+
+fn dot_node(valve: &Valve, is_start: bool) -> String {
+    let mut attributes = vec![format!(
+        "label=\"{}\\nflow={}\"",
+        valve.name, valve.flow_rate
+    )];
+    if valve.flow_rate > 0 {
+        attributes.push("style=filled, fillcolor=orange".to_string());
+    }
+    if is_start {
+        attributes.push("shape=doublecircle".to_string());
+    }
+    format!("  {} [{}];\n", valve.name, attributes.join(", "))
+}
+
+/// Exports the tunnels in the Graphviz DOT format. Two-way tunnels are drawn
+/// as a single line, one-way tunnels as arrows.
+pub fn tunnels_to_dot(volcano: &Volcano) -> String {
+    let valves = &volcano.valves;
+    let mut dot = "digraph volcano {\n".to_string();
+    for valve in valves {
+        dot += &dot_node(valve, valve.index == volcano.start_index);
+    }
+    for valve in valves {
+        for &to_index in &valve.tunnels {
+            let is_two_way = valves[to_index].tunnels.contains(&valve.index);
+            if !is_two_way {
+                dot += &format!("  {} -> {};\n", valve.name, valves[to_index].name);
+            } else if valve.index < to_index {
+                dot += &format!(
+                    "  {} -> {} [dir=none];\n",
+                    valve.name, valves[to_index].name
+                );
+            }
+        }
+    }
+    dot += "}\n";
+    dot
+}
+
+/// Exports the compressed graph used by the solvers in the Graphviz DOT
+/// format: only AA and the valves with a positive flow rate, with an edge
+/// between each pair labeled with their distance (from `find_all_distances`),
+/// unless there's no path between them.
+pub fn distances_to_dot(volcano: &Volcano) -> String {
+    let useful = volcano
+        .valves
+        .iter()
+        .filter(|valve| valve.flow_rate > 0 || valve.index == volcano.start_index)
+        .collect_vec();
+    let mut dot = "graph distances {\n".to_string();
+    for valve in &useful {
+        dot += &dot_node(valve, valve.index == volcano.start_index);
+    }
+    for (from, to) in useful.iter().tuple_combinations() {
+        if from.distance[to.index] == UNREACHABLE {
+            continue;
+        }
+        dot += &format!(
+            "  {} -- {} [label=\"{}\"];\n",
+            from.name, to.name, from.distance[to.index]
+        );
+    }
+    dot += "}\n";
+    dot
+}
+
+/// Whether `part1` and `part2` use the bitmask DP solver rather than branch
+/// and bound
+static USE_BITMASK_SOLVER: AtomicBool = AtomicBool::new(false);
+
+/// Selects the solver used by `part1` and `part2` (branch and bound by default)
+pub fn set_solver(solver: Solver) {
+    USE_BITMASK_SOLVER.store(solver == Solver::Bitmask, Relaxed);
+}
+
+fn selected_solver() -> Solver {
+    match USE_BITMASK_SOLVER.load(Relaxed) {
+        true => Solver::Bitmask,
+        false => Solver::BranchAndBound,
+    }
+}
+
+pub fn part1(volcano: &Volcano) -> i32 {
+    max_pressure(volcano, 30, 1, selected_solver())
+}
+
+pub fn part2(volcano: &Volcano) -> i32 {
+    max_pressure(volcano, 26, 2, selected_solver())
+}
+
+pub const REPL_HELP: &str = "\
+valves            list the valves, their flow rate and their neighbors
+distances         distance matrix between AA and the valves with a positive flow
+pressure [minutes] [actors] [bnb|dp]
+                  max pressure release (default 30 minutes and 1 actor), using
+                  branch and bound (the default) or the bitmask DP solver
+compare [minutes] [actors]
+                  run both solvers and check that they agree
+plan [minutes] [actors]
+                  show the valves opened by each actor in an optimal plan
+timeline [minutes] [actors]
+                  show what happens during each minute of an optimal plan
+stats             number of valves, connected components, diameter...
+dot [tunnels|distances] [path]
+                  export the tunnels, or the distances between AA and the
+                  valves with a positive flow, in Graphviz DOT format";
+
+pub fn repl_command(volcano: &mut Volcano, command: &str, args: &[&str]) -> CommandResult {
+    let result = match command {
+        "valves" => Ok(volcano
+            .valves
+            .iter()
+            .map(|valve| {
+                let neighbors = valve
+                    .tunnels
+                    .iter()
+                    .map(|&index| &volcano.valves[index].name)
+                    .join(", ");
+                format!("{} flow={:<3} -> {neighbors}", valve.name, valve.flow_rate)
+            })
+            .join("\n")),
+        "distances" => {
+            let useful = volcano
+                .valves
+                .iter()
+                .filter(|valve| valve.flow_rate > 0 || valve.index == volcano.start_index)
+                .collect_vec();
+            let header = useful
+                .iter()
+                .map(|valve| format!("{:>3}", valve.name))
+                .join("");
+            let rows = useful.iter().map(|from| {
+                let row = useful
+                    .iter()
+                    .map(|to| match from.distance[to.index] {
+                        UNREACHABLE => "  -".to_string(),
+                        distance => format!("{distance:>3}"),
+                    })
+                    .join("");
+                format!("{}{row}", from.name)
+            });
+            Ok([format!("  {header}")].into_iter().chain(rows).join("\n"))
+        }
+        "pressure" => arg(args, 0, Some(30)).and_then(|minutes| {
+            let num_actors = arg(args, 1, Some(1))?;
+            let solver = arg(args, 2, Some(Solver::BranchAndBound))?;
+            Ok(max_pressure(volcano, minutes, num_actors, solver).to_string())
+        }),
+        "compare" => arg(args, 0, Some(30)).and_then(|minutes| {
+            let num_actors = arg(args, 1, Some(1))?;
+            let results = [Solver::BranchAndBound, Solver::Bitmask].map(|solver| {
+                let start_time = Instant::now();
+                let pressure = max_pressure(volcano, minutes, num_actors, solver);
+                (solver, pressure, start_time.elapsed())
+            });
+            let report = results
+                .iter()
+                .map(|(solver, pressure, elapsed)| {
+                    format!("{solver:?}: {pressure} ({elapsed:.1?})")
+                })
+                .join("\n");
+            if results[0].1 == results[1].1 {
+                Ok(report)
+            } else {
+                Err(format!("the solvers disagree!\n{report}"))
+            }
+        }),
+        "plan" | "timeline" => arg(args, 0, Some(30)).and_then(|minutes| {
+            let num_actors = arg(args, 1, Some(1))?;
+            let plan = optimal_plan(volcano, minutes, num_actors);
+            Ok(match command {
+                "plan" => plan.to_string(),
+                _ => plan.timeline(),
+            })
+        }),
+        "stats" => Ok(network_stats(volcano).to_string()),
+        "dot" => {
+            let dot = match args.first().copied().unwrap_or("tunnels") {
+                "tunnels" => Ok(tunnels_to_dot(volcano)),
+                "distances" => Ok(distances_to_dot(volcano)),
+                graph => Err(format!(
+                    "unknown graph {graph}, expected tunnels or distances"
+                )),
+            };
+            dot.and_then(|dot| match args.get(1) {
+                Some(path) => fs::write(path, dot)
+                    .map(|_| format!("saved to {path}"))
+                    .map_err(|error| error.to_string()),
+                None => Ok(dot),
+            })
+        }
+        _ => return None,
+    };
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+Valve BB has flow rate=13; tunnels lead to valves CC, AA
+Valve CC has flow rate=2; tunnels lead to valves DD, BB
+Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
+Valve EE has flow rate=3; tunnels lead to valves FF, DD
+Valve FF has flow rate=0; tunnels lead to valves EE, GG
+Valve GG has flow rate=0; tunnels lead to valves FF, HH
+Valve HH has flow rate=22; tunnel leads to valve GG
+Valve II has flow rate=0; tunnels lead to valves AA, JJ
+Valve JJ has flow rate=21; tunnel leads to valve II";
+
+    #[test]
+    fn solvers_agree_on_example() {
+        let volcano = parse(EXAMPLE);
+        for (minutes, num_actors, expected) in [(30, 1, 1651), (26, 2, 1707)] {
+            for solver in [Solver::BranchAndBound, Solver::Bitmask] {
+                assert_eq!(
+                    max_pressure(&volcano, minutes, num_actors, solver),
+                    expected,
+                    "{solver:?} with {num_actors} actors"
+                );
+            }
+        }
+        // an actor has to retire early for the branch and bound to find these
+        for (minutes, num_actors) in [(26, 3), (20, 4)] {
+            assert_eq!(
+                max_pressure(&volcano, minutes, num_actors, Solver::BranchAndBound),
+                max_pressure(&volcano, minutes, num_actors, Solver::Bitmask),
+                "{num_actors} actors"
+            );
+        }
+    }
+
+    #[test]
+    fn plan_avoids_unreachable_moves() {
+        // both valves are dead ends, so each actor can only open one of them
+        let input = "\
+Valve AA has flow rate=0; tunnels lead to valves BB, CC
+Valve BB has flow rate=5; tunnel leads to valve BB
+Valve CC has flow rate=10; tunnel leads to valve CC";
+        let volcano = parse(input);
+        assert_eq!(max_pressure(&volcano, 30, 1, Solver::BranchAndBound), 280);
+        let plan = optimal_plan(&volcano, 30, 1);
+        assert_eq!(plan.pressure, 280);
+        assert_eq!(plan.actors[0].len(), 1);
+        assert_eq!(optimal_plan(&volcano, 30, 2).pressure, 280 + 140);
+    }
+
+    #[test]
+    fn unreachable_useful_valve_is_rejected() {
+        let input = "\
+Valve AA has flow rate=0; tunnel leads to valve BB
+Valve BB has flow rate=5; tunnel leads to valve AA
+Valve CC has flow rate=10; tunnel leads to valve CC";
+        assert_eq!(
+            try_parse(input).err(),
+            Some(VolcanoError::UnreachableValve("CC".to_string()))
+        );
+    }
 }
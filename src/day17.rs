@@ -1,123 +1,360 @@
+use super::repl::{arg, CommandResult};
 use hashbrown::HashMap;
 use itertools::Itertools;
+use num::PrimInt;
 use std::cmp::max;
+use std::hash::Hash;
+use std::sync::OnceLock;
 
-const WIDTH: usize = 7;
-const MAX_HEIGHT: usize = 10_000;
-const NUM_TOP_ROWS: i64 = 10; // number of top rows to look at to detect repetion
+const PART1_ROCKS: u64 = 2022;
+const PART2_ROCKS: u64 = 1_000_000_000_000;
+const DEFAULT_WIDTH: usize = 7;
+/// The cycle search gives up after this many rocks. The puzzle inputs repeat
+/// after a couple thousand rocks.
+const MAX_SIMULATED_ROCKS: usize = 100_000;
+/// States whose surface profile is deeper than this aren't used as cycle keys,
+/// which keeps the keys (and the rows kept in memory) bounded. The puzzle
+/// inputs stay under 100 rows.
+const MAX_PROFILE_DEPTH: usize = 128;
+/// The rows below the surface profile are dropped in batches of this many
+const SCROLL_ROWS: usize = 1024;
+const DEFAULT_SHAPES: [&str; 5] = [
+    "####",          // -
+    ".#.\n###\n.#.", // +
+    "..#\n..#\n###", // ┛
+    "#\n#\n#\n#",    // |
+    "##\n##",        // ■
+];
 
-fn drop_rock(
+/// Rows are stored as bitmasks, where bit x is set if cell x is solid. The
+/// smallest integer type that fits the chamber is used, e.g. u8 for width 7.
+trait Row: PrimInt + Hash {}
+
+impl<T: PrimInt + Hash> Row for T {}
+
+/// Whether the shape (whose leftmost column is at x and bottom row at y)
+/// overlaps with a rock. Rows above the top of the grid are empty.
+fn collides<R: Row>(grid: &[R], shape: &[R], x: usize, y: usize) -> bool {
+    shape
+        .iter()
+        .zip(grid.iter().skip(y))
+        .any(|(&shape_row, &row)| (shape_row << x) & row != R::zero())
+}
+
+fn drop_rock<R: Row>(
     jets: &[i64],
-    shape: &[Vec<bool>],
-    grid: &mut [Vec<bool>],
+    shape: &Shape<R>,
+    width: usize,
+    grid: &mut Vec<R>,
     time: &mut i64,
     height: &mut i64,
 ) {
-    let shape_height = shape.len() as i64;
-    let shape_width = shape[0].len() as i64;
     let mut x = 2;
-    let mut y = *height + 3;
-    'falling: loop {
+    let mut y = *height as usize + 3;
+    loop {
         let jet = jets[*time as usize % jets.len()];
         *time += 1;
-        'slide_and_drop: for (dx, dy) in [(jet, 0), (0, -1)] {
-            let nx = x + dx;
-            let ny = y + dy;
-            if ny < 0 && dy == -1 {
-                break 'falling;
-            }
-            if nx < 0 || nx + shape_width > 7 {
-                continue;
-            }
-            for (cell_y, row) in shape.iter().enumerate() {
-                for (cell_x, &is_solid) in row.iter().enumerate() {
-                    if is_solid && grid[ny as usize + cell_y][nx as usize + cell_x] {
-                        if dy == -1 {
-                            break 'falling;
-                        } else {
-                            continue 'slide_and_drop;
-                        }
-                    }
-                }
-            }
-            x = nx;
-            y = ny;
+        let nx = x as i64 + jet;
+        if nx >= 0
+            && nx as usize + shape.width <= width
+            && !collides(grid, &shape.rows, nx as usize, y)
+        {
+            x = nx as usize;
+        }
+        if y == 0 || collides(grid, &shape.rows, x, y - 1) {
+            break;
         }
+        y -= 1;
     }
-    for (cell_y, row) in shape.iter().enumerate() {
-        for (cell_x, &is_solid) in row.iter().enumerate() {
-            if is_solid {
-                grid[y as usize + cell_y][x as usize + cell_x] = true;
+    *height = max(*height, (y + shape.rows.len()) as i64);
+    if grid.len() < *height as usize {
+        grid.resize(*height as usize, R::zero());
+    }
+    for (cell_y, &shape_row) in shape.rows.iter().enumerate() {
+        grid[y + cell_y] = grid[y + cell_y] | (shape_row << x);
+    }
+}
+
+/// The empty cells that a falling rock can still reach, as one bitmask per
+/// row, from the top of the tower down to the last row with a reachable cell.
+/// Rocks only move sideways and down, so this is a flood fill from the row
+/// above the tower, and everything outside of it can never change the future:
+/// two towers with the same profile (and the same next jet and shape) grow in
+/// exactly the same way. Returns `None` if the profile is deeper than
+/// `MAX_PROFILE_DEPTH` rows.
+fn surface_profile<R: Row>(grid: &[R], height: i64, full_row: R) -> Option<Vec<R>> {
+    let mut profile = vec![];
+    let mut reachable = full_row;
+    for &row in grid[..height as usize].iter().rev() {
+        let empty = full_row & !row;
+        reachable = reachable & empty;
+        // spread sideways through the empty cells
+        loop {
+            let spread = (reachable | (reachable << 1) | (reachable >> 1)) & empty;
+            if spread == reachable {
+                break;
             }
+            reachable = spread;
         }
+        if reachable == R::zero() {
+            break;
+        }
+        if profile.len() == MAX_PROFILE_DEPTH {
+            return None;
+        }
+        profile.push(reachable);
     }
-    *height = max(*height, y + shape_height);
+    Some(profile)
 }
 
-fn get_height(jets: &[i64], shapes: &[Vec<Vec<bool>>], num_rocks: i64) -> i64 {
-    let mut grid = vec![vec![false; WIDTH]; MAX_HEIGHT];
+/// Simulates the rocks until the state repeats, recording the height of the
+/// tower after each rock. Stops after `max_rocks` rocks if there's no cycle
+/// by then (e.g., if the jets never push any rock into part of a very wide
+/// chamber, so its open floor stays reachable). The grid only keeps the rows
+/// from the bottom of the surface profile up, and `base` is the height of its
+/// first row.
+fn simulate<R: Row>(chamber: &Chamber, max_rocks: usize) -> TowerModel {
+    let Chamber {
+        jets,
+        shapes,
+        width,
+        ..
+    } = chamber;
+    let shapes = shapes
+        .iter()
+        .map(|shape| Shape {
+            rows: shape
+                .iter()
+                .map(|row| {
+                    row.iter().rev().fold(R::zero(), |mask, &is_rock| {
+                        (mask << 1) | R::from(is_rock as u8).unwrap()
+                    })
+                })
+                .collect_vec(),
+            width: shape[0].len(),
+        })
+        .collect_vec();
+    let full_row = (0..*width).fold(R::zero(), |mask, _| (mask << 1) | R::one());
+    let mut grid = vec![];
+    let mut base = 0;
     let mut time = 0;
     let mut height = 0;
-    let mut index = 0;
+    let mut heights = vec![0];
     let mut past_states = HashMap::new();
-    let mut additional_height = 0;
-    while index < num_rocks {
-        let shape = &shapes[index as usize % shapes.len()];
-        drop_rock(jets, shape, &mut grid, &mut time, &mut height);
-        let top_grid = if height >= NUM_TOP_ROWS {
-            grid[(height - NUM_TOP_ROWS) as usize..height as usize]
-                .iter()
-                .cloned()
-                .collect_vec()
-        } else {
-            vec![]
+    for index in 1..=max_rocks {
+        let shape = &shapes[(index - 1) % shapes.len()];
+        drop_rock(jets, shape, *width, &mut grid, &mut time, &mut height);
+        heights.push(base + height);
+        let Some(profile) = surface_profile(&grid, height, full_row) else {
+            continue;
         };
-        let state = (
-            time % jets.len() as i64,
-            index as usize % shapes.len(),
-            top_grid.clone(),
-        );
-        if let Some(&(old_index, old_height, _)) = past_states.get(&state) {
-            if additional_height == 0 {
-                let index_diff = index - old_index;
-                let height_diff = height - old_height;
-                let repeats = (num_rocks - old_index) / index_diff - 1;
-                index += repeats * index_diff;
-                additional_height = repeats * height_diff;
-            }
-        } else {
-            past_states.insert(state, (index, height, top_grid.clone()));
+        // the row just below the profile still blocks the rocks, but the rows
+        // under it can never be reached again
+        let unreachable_rows = (height as usize).saturating_sub(profile.len() + 1);
+        if unreachable_rows >= SCROLL_ROWS {
+            grid.drain(..unreachable_rows);
+            base += unreachable_rows as i64;
+            height -= unreachable_rows as i64;
         }
-        index += 1;
+        let state = (time % jets.len() as i64, index % shapes.len(), profile);
+        if let Some(&old_index) = past_states.get(&state) {
+            return TowerModel {
+                heights,
+                cycle: Some((old_index, index - old_index)),
+            };
+        }
+        past_states.insert(state, index);
+    }
+    TowerModel {
+        heights,
+        cycle: None,
     }
-    height + additional_height
 }
 
-pub fn run(input: &str) {
-    let jets = input
+/// The height of the tower after any number of rocks, from a single
+/// simulation: once the tower is in a cycle, it grows by the same height
+/// every `period` rocks.
+#[derive(Debug, Clone)]
+pub struct TowerModel {
+    /// heights[n] is the height after n rocks, up to the end of the first cycle
+    heights: Vec<i64>,
+    /// (start, period)
+    cycle: Option<(usize, usize)>,
+}
+
+impl TowerModel {
+    /// Simulates at most `max_rocks` rocks to find a cycle
+    pub fn new(chamber: &Chamber, max_rocks: usize) -> Self {
+        // use the smallest row type that fits the chamber
+        match chamber.width {
+            0..=8 => simulate::<u8>(chamber, max_rocks),
+            9..=16 => simulate::<u16>(chamber, max_rocks),
+            17..=32 => simulate::<u32>(chamber, max_rocks),
+            33..=64 => simulate::<u64>(chamber, max_rocks),
+            _ => simulate::<u128>(chamber, max_rocks),
+        }
+    }
+
+    /// The number of rocks after which the tower starts repeating, if a cycle
+    /// was found
+    pub fn cycle_start(&self) -> Option<usize> {
+        self.cycle.map(|(start, _)| start)
+    }
+
+    /// The number of rocks in each cycle, if a cycle was found
+    pub fn period(&self) -> Option<usize> {
+        self.cycle.map(|(_, period)| period)
+    }
+
+    /// The height gained during each cycle, if a cycle was found
+    pub fn cycle_height(&self) -> Option<i64> {
+        self.cycle
+            .map(|(start, period)| self.heights[start + period] - self.heights[start])
+    }
+
+    /// Returns `None` if there's no cycle and `num_rocks` is beyond the rocks
+    /// that were simulated
+    pub fn height_after(&self, num_rocks: u64) -> Option<i64> {
+        if let Some(&height) = self.heights.get(num_rocks as usize) {
+            return Some(height);
+        }
+        let (start, period) = self.cycle?;
+        let (repeats, remainder) = (
+            (num_rocks - start as u64) / period as u64,
+            (num_rocks - start as u64) % period as u64,
+        );
+        Some(self.heights[start + remainder as usize] + repeats as i64 * self.cycle_height()?)
+    }
+}
+
+/// A rock shape, as one bitmask per row, from bottom to top, with its leftmost
+/// column at bit 0
+struct Shape<R> {
+    rows: Vec<R>,
+    width: usize,
+}
+
+pub struct Chamber {
+    jets: Vec<i64>,
+    /// rows from bottom to top
+    shapes: Vec<Vec<Vec<bool>>>,
+    width: usize,
+    /// built on first use, and shared by both parts
+    model: OnceLock<TowerModel>,
+}
+
+impl Chamber {
+    /// The model used for part 2 and the REPL, with a cycle search of at most
+    /// `MAX_SIMULATED_ROCKS` rocks
+    pub fn model(&self) -> &TowerModel {
+        self.model
+            .get_or_init(|| TowerModel::new(self, MAX_SIMULATED_ROCKS))
+    }
+}
+
+/// The input is the jet pattern, optionally followed by blank-line separated
+/// sections: "width: <n>" for the width of the chamber (at most 128), and the
+/// shapes of the rocks in the order they fall, drawn with '#' and '.' as in
+/// the puzzle statement. The defaults are the puzzle's width of 7 and its five
+/// shapes.
+pub fn parse(input: &str) -> Chamber {
+    let mut sections = input.split("\n\n").map(str::trim);
+    let jets = sections
+        .next()
+        .unwrap()
         .bytes()
-        .map(|b| if b == b'<' { -1 } else { 1 })
+        .map(|b| match b {
+            b'<' => -1,
+            b'>' => 1,
+            _ => panic!("invalid jet {}", b as char),
+        })
         .collect_vec();
-    let shapes = [
-        "####",          // -
-        ".#.\n###\n.#.", // +
-        "..#\n..#\n###", // ┛
-        "#\n#\n#\n#",    // |
-        "##\n##",        // ■
-    ]
-    .iter()
-    .map(|shape| {
-        shape
-            .lines()
-            .map(|row| row.bytes().map(|b| b == b'#').collect_vec())
-            .rev()
-            .collect_vec()
-    })
-    .collect_vec();
-
-    let height = get_height(&jets, &shapes, 2022);
-    println!("{}", height);
-
-    let height = get_height(&jets, &shapes, 1_000_000_000_000);
-    println!("{}", height);
+    assert!(!jets.is_empty(), "no jets");
+    let mut width = DEFAULT_WIDTH;
+    let mut shapes = vec![];
+    for section in sections.filter(|section| !section.is_empty()) {
+        if let Some(value) = section.strip_prefix("width:") {
+            width = value.trim().parse().expect("invalid width");
+            assert!(width <= 128, "the chamber can be at most 128 units wide");
+        } else {
+            shapes.push(section);
+        }
+    }
+    if shapes.is_empty() {
+        shapes = DEFAULT_SHAPES.to_vec();
+    }
+    let shapes = shapes
+        .iter()
+        .map(|shape| {
+            let rows = shape
+                .lines()
+                .map(|row| row.trim().bytes().map(|b| b == b'#').collect_vec())
+                .rev()
+                .collect_vec();
+            let shape_width = rows[0].len();
+            assert!(
+                rows.iter().all(|row| row.len() == shape_width),
+                "the rows of a shape must have the same length"
+            );
+            assert!(
+                rows.iter().flatten().any(|&is_rock| is_rock),
+                "a shape must have at least one rock cell"
+            );
+            assert!(
+                shape_width + 2 <= width,
+                "a shape of width {shape_width} doesn't fit in the chamber"
+            );
+            rows
+        })
+        .collect_vec();
+    Chamber {
+        jets,
+        shapes,
+        width,
+        model: OnceLock::new(),
+    }
+}
+
+pub fn part1(chamber: &Chamber) -> i64 {
+    // the model covers these rocks even if the tower never repeats, since it
+    // then simulates MAX_SIMULATED_ROCKS rocks
+    chamber.model().height_after(PART1_ROCKS).unwrap()
+}
+
+pub fn part2(chamber: &Chamber) -> i64 {
+    chamber
+        .model()
+        .height_after(PART2_ROCKS)
+        .unwrap_or_else(|| panic!("no cycle in the first {MAX_SIMULATED_ROCKS} rocks"))
+}
+
+pub const REPL_HELP: &str = "\
+cycle             the number of rocks before the tower repeats, and the period
+height <rocks>    height of the tower after the given number of rocks";
+
+pub fn repl_command(chamber: &mut Chamber, command: &str, args: &[&str]) -> CommandResult {
+    let model = chamber.model();
+    let result = match command {
+        "cycle" => Ok(match model.cycle {
+            Some((start, period)) => format!(
+                "start: {start} rocks (height {}), period: {period} rocks (height {})",
+                model.heights[start],
+                model.cycle_height().unwrap()
+            ),
+            None => format!("no cycle in the first {MAX_SIMULATED_ROCKS} rocks"),
+        }),
+        "height" => arg(args, 0, None).and_then(|num_rocks| {
+            model
+                .height_after(num_rocks)
+                .map(|height| height.to_string())
+                .ok_or_else(|| {
+                    format!(
+                        "no cycle, and only {} rocks were simulated",
+                        model.heights.len() - 1
+                    )
+                })
+        }),
+        _ => return None,
+    };
+    Some(result)
 }
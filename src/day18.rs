@@ -1,6 +1,6 @@
 use hashbrown::HashSet;
-use std::collections::VecDeque;
 use itertools::Itertools;
+use std::collections::VecDeque;
 
 fn is_neighbor(droplet1: &[i64], droplet2: &[i64]) -> bool {
     let mut distance = 0;
@@ -10,7 +10,7 @@ fn is_neighbor(droplet1: &[i64], droplet2: &[i64]) -> bool {
     distance == 1
 }
 
-fn num_free_surfaces(droplets: &HashSet<[i64; 3]>) -> usize {
+pub fn part1(droplets: &HashSet<[i64; 3]>) -> usize {
     let num_neighbors = droplets
         .iter()
         .combinations(2)
@@ -19,7 +19,7 @@ fn num_free_surfaces(droplets: &HashSet<[i64; 3]>) -> usize {
     droplets.len() * 6 - num_neighbors * 2
 }
 
-fn num_surfaces_outside(droplets: &HashSet<[i64; 3]>) -> usize {
+pub fn part2(droplets: &HashSet<[i64; 3]>) -> usize {
     let max_coords = (0..3)
         .map(|axis| droplets.iter().map(|coords| coords[axis]).max().unwrap())
         .collect_vec();
@@ -30,7 +30,7 @@ fn num_surfaces_outside(droplets: &HashSet<[i64; 3]>) -> usize {
         if visited.contains(&node) {
             continue;
         }
-        visited.insert(node.clone());
+        visited.insert(node);
         'next_neighbor: for offset in [
             [0, 0, -1],
             [0, 0, 1],
@@ -56,18 +56,15 @@ fn num_surfaces_outside(droplets: &HashSet<[i64; 3]>) -> usize {
     num_surfaces
 }
 
-pub fn run(input: &str) {
-    let droplets: HashSet<[i64; 3]> = input
+pub fn parse(input: &str) -> HashSet<[i64; 3]> {
+    input
         .lines()
-        .map(|line| line
-            .split(',')
-            .map(|v| v.parse().unwrap()).collect_vec().try_into().unwrap()
-        )
-        .collect();
-
-    let surface = num_free_surfaces(&droplets);
-    println!("{}", surface);
-
-    let surface = num_surfaces_outside(&droplets);
-    println!("{}", surface);
+        .map(|line| {
+            line.split(',')
+                .map(|v| v.parse().unwrap())
+                .collect_vec()
+                .try_into()
+                .unwrap()
+        })
+        .collect()
 }
@@ -1,112 +1,529 @@
-use super::utils::parse_int_vecs;
+use super::repl::{arg, CommandResult};
+use hashbrown::HashSet;
 use itertools::Itertools;
 use rayon::prelude::*;
-use std::cmp::max;
+use regex::Regex;
+use std::cmp::{max, Reverse};
+use std::fmt;
+use std::str::FromStr;
 
-/// the number of resources required for each robot
-type Blueprint = [[i64; 4]; 4];
+/// The max number of resource types, so that the states fit in arrays
+const MAX_RESOURCES: usize = 8;
+
+/// An amount of each resource, or a number of robots of each type
+type Amounts = [i64; MAX_RESOURCES];
+
+/// There is one robot type per resource type: robot i collects resource i.
+/// We start with a single robot of the first type, and try to collect as much
+/// as possible of the target resource (geodes).
+#[derive(Debug, Clone)]
+pub struct Blueprint {
+    pub id: i64,
+    pub resources: Vec<String>,
+    /// the resources required to build each robot
+    pub costs: Vec<Amounts>,
+    pub target: usize,
+}
+
+impl Blueprint {
+    fn num_resources(&self) -> usize {
+        self.resources.len()
+    }
+
+    /// It's useless to have more robots of a type than the most we can spend
+    /// in one minute, except for the target resource
+    fn max_robots(&self) -> Amounts {
+        let mut max_robots = [0; MAX_RESOURCES];
+        for resource in 0..self.num_resources() {
+            max_robots[resource] = if resource == self.target {
+                i64::MAX
+            } else {
+                self.costs.iter().map(|cost| cost[resource]).max().unwrap()
+            };
+        }
+        max_robots
+    }
+
+    /// The order in which the robots are tried: target first, then the others
+    /// from last to first (e.g., geode > obsidian > clay > ore)
+    fn priority(&self) -> Vec<usize> {
+        let others = (0..self.num_resources())
+            .rev()
+            .filter(|&robot| robot != self.target);
+        [self.target].into_iter().chain(others).collect()
+    }
+
+    fn robot_name(&self, robot: usize) -> String {
+        match self.resources[robot].as_str() {
+            "geode" => "geode-cracking robot".to_string(),
+            name => format!("{name}-collecting robot"),
+        }
+    }
+
+    /// e.g., "2 ore and 7 obsidian"
+    fn describe_cost(&self, robot: usize) -> String {
+        (0..self.num_resources())
+            .filter(|&resource| self.costs[robot][resource] > 0)
+            .map(|resource| {
+                format!(
+                    "{} {}",
+                    self.costs[robot][resource], self.resources[resource]
+                )
+            })
+            .join(" and ")
+    }
+}
 
 fn ceil_div(a: i64, b: i64) -> i64 {
     (a + b - 1) / b
 }
 
-/// robots: number of robots of each type (ore, clay, obsidian, geode)
-/// resources: number of resources of each type
-fn max_opened_geodes(
-    blueprint: Blueprint,
-    robots: [i64; 4],
-    resources: [i64; 4],
-    time_left: i64,
-    max_geodes: i64,
-) -> i64 {
-    let geodes_if_just_wait = resources[3] + time_left * robots[3];
-    let mut max_geodes = max(max_geodes, geodes_if_just_wait);
-    let upper_bound = resources[3] + time_left * robots[3] + time_left * (time_left - 1) / 2;
-    if upper_bound <= max_geodes {
-        return max_geodes;
-    } // pruning
-    for next_robot in (0..4).rev() {
-        // priority: geode > obsidian > clay > ore
-        let max_robots = match next_robot {
-            0 => (0..4).map(|index| blueprint[index][0]).max().unwrap(), // max ore needed for any robot
-            1 => blueprint[2][1], // clay required for obsidian
-            2 => blueprint[3][2], // obsidian required for geode
-            3 => i64::MAX,        // unlimited power geodes
-            _ => unreachable!(),
-        };
-        if robots[next_robot] >= max_robots {
-            continue;
+fn initial_robots() -> Amounts {
+    let mut robots = [0; MAX_RESOURCES];
+    robots[0] = 1;
+    robots
+}
+
+/// The exact depth-first search for one blueprint
+struct Search<'a> {
+    blueprint: &'a Blueprint,
+    max_robots: Amounts,
+    priority: Vec<usize>,
+    /// (robots, resources, time left) already explored, if memoization is on
+    visited: Option<HashSet<(Amounts, Amounts, i64)>>,
+    /// the robots built so far, as (time left once built, robot type)
+    builds: Vec<(i64, usize)>,
+    /// the robots built in the best plan found so far
+    best_builds: Vec<(i64, usize)>,
+}
+
+impl Search<'_> {
+    /// robots: number of robots of each type
+    /// resources: number of resources of each type
+    fn max_opened_geodes(
+        &mut self,
+        robots: Amounts,
+        resources: Amounts,
+        time_left: i64,
+        max_geodes: i64,
+    ) -> i64 {
+        let target = self.blueprint.target;
+        let geodes_if_just_wait = resources[target] + time_left * robots[target];
+        if geodes_if_just_wait > max_geodes {
+            self.best_builds.clone_from(&self.builds);
         }
-        let time_to_build = (0..4)
-            .map(|index| {
-                let have = resources[index];
-                let need = blueprint[next_robot][index];
-                let rate = robots[index];
-                if need <= have {
-                    1
-                } else if rate == 0 {
-                    time_left
-                } else {
-                    ceil_div(need - have, rate) + 1
+        let mut max_geodes = max(max_geodes, geodes_if_just_wait);
+        let upper_bound = geodes_if_just_wait + time_left * (time_left - 1) / 2;
+        if upper_bound <= max_geodes {
+            return max_geodes;
+        } // pruning
+        if let Some(visited) = &mut self.visited {
+            // max_geodes only grows, so a state explored before can't do better now
+            if !visited.insert((robots, resources, time_left)) {
+                return max_geodes;
+            }
+        }
+        let num_resources = self.blueprint.num_resources();
+        for priority_index in 0..self.priority.len() {
+            let next_robot = self.priority[priority_index];
+            if robots[next_robot] >= self.max_robots[next_robot] {
+                continue;
+            }
+            let cost = self.blueprint.costs[next_robot];
+            let time_to_build = (0..num_resources)
+                .map(|index| {
+                    let have = resources[index];
+                    let need = cost[index];
+                    let rate = robots[index];
+                    if need <= have {
+                        1
+                    } else if rate == 0 {
+                        time_left
+                    } else {
+                        ceil_div(need - have, rate) + 1
+                    }
+                })
+                .max()
+                .unwrap_or(time_left);
+            let new_time_left = time_left - time_to_build;
+            if new_time_left > 0 {
+                let mut new_resources = resources;
+                for index in 0..num_resources {
+                    new_resources[index] += robots[index] * time_to_build - cost[index];
                 }
-            })
-            .max()
-            .unwrap_or(time_left);
-        let new_time_left = time_left - time_to_build;
-        if new_time_left > 0 {
-            let mut new_resources = resources;
-            for index in 0..4 {
-                new_resources[index] +=
-                    robots[index] * time_to_build - blueprint[next_robot][index];
+                let mut new_robots = robots;
+                new_robots[next_robot] += 1;
+                self.builds.push((new_time_left, next_robot));
+                let branch_max_geodes =
+                    self.max_opened_geodes(new_robots, new_resources, new_time_left, max_geodes);
+                self.builds.pop();
+                max_geodes = max(max_geodes, branch_max_geodes);
             }
-            let mut new_robots = robots;
-            new_robots[next_robot] += 1;
-            let branch_max_geodes = max_opened_geodes(
+        }
+        max_geodes
+    }
+}
+
+/// Approximate search, minute by minute, keeping only the `width` most
+/// promising states after each minute. Returns the number of geodes and the
+/// robots built, as (time left once built, robot type).
+fn beam_search(blueprint: &Blueprint, minutes: i64, width: usize) -> (i64, Vec<(i64, usize)>) {
+    let num_resources = blueprint.num_resources();
+    let max_robots = blueprint.max_robots();
+    let priority = blueprint.priority();
+    let target = blueprint.target;
+    let mut states = vec![(initial_robots(), [0; MAX_RESOURCES], vec![])];
+    for time_left in (0..minutes).rev() {
+        let mut next_states = vec![];
+        for (robots, resources, builds) in &states {
+            let mut collected = *resources;
+            for index in 0..num_resources {
+                collected[index] += robots[index];
+            }
+            next_states.push((*robots, collected, builds.clone()));
+            for robot in 0..num_resources {
+                let cost = &blueprint.costs[robot];
+                let affordable = (0..num_resources).all(|index| resources[index] >= cost[index]);
+                if affordable && robots[robot] < max_robots[robot] {
+                    let mut new_resources = collected;
+                    for index in 0..num_resources {
+                        new_resources[index] -= cost[index];
+                    }
+                    let mut new_robots = *robots;
+                    new_robots[robot] += 1;
+                    let mut new_builds = builds.clone();
+                    new_builds.push((time_left, robot));
+                    next_states.push((new_robots, new_resources, new_builds));
+                }
+            }
+        }
+        // most promising first: the most geodes if we just wait, then the most
+        // robots, in order of priority
+        next_states.sort_by_cached_key(|(robots, resources, _)| {
+            let geodes_if_just_wait = resources[target] + robots[target] * time_left;
+            let robots = priority.iter().map(|&robot| robots[robot]).collect_vec();
+            Reverse((geodes_if_just_wait, robots, *resources))
+        });
+        next_states.dedup_by_key(|(robots, resources, _)| (*robots, *resources));
+        next_states.truncate(width);
+        states = next_states;
+    }
+    states
+        .into_iter()
+        .map(|(_, resources, builds)| (resources[target], builds))
+        .max_by_key(|(geodes, _)| *geodes)
+        .unwrap()
+}
+
+/// How to search for the best plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strategy {
+    /// Depth-first search with branch and bound
+    #[default]
+    Exact,
+    /// Same, but skipping the states that were already explored
+    Memoized,
+    /// Approximate beam search with the given width, for long horizons
+    Beam(usize),
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match (name, name.split_once('=')) {
+            ("exact", _) => Ok(Strategy::Exact),
+            ("memo", _) => Ok(Strategy::Memoized),
+            ("beam", _) => Ok(Strategy::Beam(1000)),
+            (_, Some(("beam", width))) => match width.parse() {
+                Ok(width) if width > 0 => Ok(Strategy::Beam(width)),
+                _ => Err(format!("invalid beam width {width}")),
+            },
+            _ => Err(format!(
+                "unknown strategy {name}, expected exact, memo, beam or beam=<width>"
+            )),
+        }
+    }
+}
+
+/// The best way to use a blueprint: which robot to build during which minute
+#[derive(Debug, Clone)]
+pub struct BuildPlan {
+    pub blueprint: Blueprint,
+    pub minutes: i64,
+    /// the amount of the target resource in the end
+    pub geodes: i64,
+    /// (minute, robot type). The robot is ready at the end of the minute.
+    pub builds: Vec<(i64, usize)>,
+}
+
+pub fn best_plan(blueprint: &Blueprint, minutes: i64, strategy: Strategy) -> BuildPlan {
+    let (geodes, builds) = match strategy {
+        Strategy::Exact | Strategy::Memoized => {
+            let mut search = Search {
                 blueprint,
-                new_robots,
-                new_resources,
-                new_time_left,
-                max_geodes,
+                max_robots: blueprint.max_robots(),
+                priority: blueprint.priority(),
+                visited: (strategy == Strategy::Memoized).then(HashSet::new),
+                builds: vec![],
+                best_builds: vec![],
+            };
+            let geodes =
+                search.max_opened_geodes(initial_robots(), [0; MAX_RESOURCES], minutes, -1);
+            (geodes, search.best_builds)
+        }
+        Strategy::Beam(width) => beam_search(blueprint, minutes, width),
+    };
+    BuildPlan {
+        blueprint: blueprint.clone(),
+        minutes,
+        geodes,
+        builds: builds
+            .into_iter()
+            .map(|(time_left, robot)| (minutes - time_left, robot))
+            .collect(),
+    }
+}
+
+fn plural(count: i64, singular: &str, plural: &str) -> String {
+    format!("{count} {}", if count == 1 { singular } else { plural })
+}
+
+fn with_article(name: &str) -> String {
+    if name.starts_with(['a', 'e', 'i', 'o', 'u']) {
+        format!("an {name}")
+    } else {
+        format!("a {name}")
+    }
+}
+
+impl BuildPlan {
+    fn build_at(&self, minute: i64) -> Option<usize> {
+        self.builds
+            .iter()
+            .find(|(build_minute, _)| *build_minute == minute)
+            .map(|&(_, robot)| robot)
+    }
+
+    /// Replays the plan, returning the robots and the resources at the end of
+    /// each minute, starting with minute 1.
+    pub fn inventory(&self) -> Vec<(Vec<i64>, Vec<i64>)> {
+        let num_resources = self.blueprint.num_resources();
+        let mut robots = initial_robots();
+        let mut resources = [0; MAX_RESOURCES];
+        (1..=self.minutes)
+            .map(|minute| {
+                let build = self.build_at(minute);
+                for index in 0..num_resources {
+                    resources[index] += robots[index];
+                    if let Some(robot) = build {
+                        resources[index] -= self.blueprint.costs[robot][index];
+                    }
+                }
+                if let Some(robot) = build {
+                    robots[robot] += 1;
+                }
+                (
+                    robots[..num_resources].to_vec(),
+                    resources[..num_resources].to_vec(),
+                )
+            })
+            .collect()
+    }
+
+    /// Tells the story of the plan, in the style of the puzzle statement
+    pub fn narrative(&self) -> String {
+        let blueprint = &self.blueprint;
+        let mut robots = initial_robots()[..blueprint.num_resources()].to_vec();
+        let inventory = self.inventory();
+        (1..=self.minutes)
+            .map(|minute| {
+                let (new_robots, resources) = &inventory[minute as usize - 1];
+                let build = self.build_at(minute);
+                let mut lines = vec![format!("== Minute {minute} ==")];
+                if let Some(robot) = build {
+                    lines.push(format!(
+                        "Spend {} to start building {}.",
+                        blueprint.describe_cost(robot),
+                        with_article(&blueprint.robot_name(robot))
+                    ));
+                }
+                for index in (0..robots.len()).filter(|&index| robots[index] > 0) {
+                    let count = robots[index];
+                    let name = &blueprint.resources[index];
+                    let robot_name = blueprint.robot_name(index);
+                    let robots = plural(count, &robot_name, &format!("{robot_name}s"));
+                    lines.push(if name == "geode" {
+                        format!(
+                            "{robots} {} {}; you now have {}.",
+                            if count == 1 { "cracks" } else { "crack" },
+                            plural(count, "geode", "geodes"),
+                            plural(resources[index], "open geode", "open geodes")
+                        )
+                    } else {
+                        format!(
+                            "{robots} {} {count} {name}; you now have {} {name}.",
+                            if count == 1 { "collects" } else { "collect" },
+                            resources[index]
+                        )
+                    });
+                }
+                if let Some(robot) = build {
+                    lines.push(format!(
+                        "The new {} is ready; you now have {} of them.",
+                        blueprint.robot_name(robot),
+                        new_robots[robot]
+                    ));
+                }
+                robots.clone_from(new_robots);
+                lines.join("\n")
+            })
+            .join("\n\n")
+    }
+}
+
+impl fmt::Display for BuildPlan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let resources = &self.blueprint.resources;
+        let target = &resources[self.blueprint.target];
+        let geodes = plural(self.geodes, target, &format!("{target}s"));
+        write!(f, "{geodes} in {} minutes", self.minutes)?;
+        let built_width = resources.iter().map(String::len).max().unwrap().max(5);
+        let names = resources.iter().join(" ");
+        let columns = |amounts: &[i64]| {
+            amounts
+                .iter()
+                .zip(resources)
+                .map(|(amount, name)| format!("{amount:<width$}", width = name.len()))
+                .join(" ")
+        };
+        write!(
+            f,
+            "\n{:indent$}{:<width$} | resources\nminute  {:<built_width$}  {names} | {names}",
+            "",
+            "robots",
+            "built",
+            indent = built_width + 10,
+            width = names.len(),
+        )?;
+        for (index, (robots, amounts)) in self.inventory().into_iter().enumerate() {
+            let minute = index as i64 + 1;
+            let built = self.build_at(minute).map_or("", |robot| &resources[robot]);
+            let row = format!(
+                "{minute:>6}  {built:<built_width$}  {} | {}",
+                columns(&robots),
+                columns(&amounts)
             );
-            max_geodes = max(max_geodes, branch_max_geodes);
+            write!(f, "\n{}", row.trim_end())?;
         }
+        Ok(())
     }
-    max_geodes
 }
 
-fn sum_of_quality_levels(blueprints: &[Blueprint]) -> i64 {
+pub fn part1(blueprints: &[Blueprint]) -> i64 {
     blueprints
         .par_iter()
-        .enumerate()
-        .map(|(index, &blueprint)| {
-            (index as i64 + 1) * max_opened_geodes(blueprint, [1, 0, 0, 0], [0, 0, 0, 0], 24, 0)
-        })
+        .map(|blueprint| blueprint.id * best_plan(blueprint, 24, Strategy::Exact).geodes)
         .sum()
 }
 
-fn product_of_max_geodes(blueprints: &[Blueprint]) -> i64 {
-    blueprints
+pub fn part2(blueprints: &[Blueprint]) -> i64 {
+    blueprints[0..blueprints.len().min(3)]
         .par_iter()
-        .map(|&blueprint| max_opened_geodes(blueprint, [1, 0, 0, 0], [0, 0, 0, 0], 32, 0))
+        .map(|blueprint| best_plan(blueprint, 32, Strategy::Exact).geodes)
         .product()
 }
 
-pub fn run(input: &str) {
-    let blueprints: Vec<Blueprint> = parse_int_vecs(input, false)
-        .into_iter()
-        .map(|cost| {
-            [
-                [cost[1], 0, 0, 0],       // ore robot
-                [cost[2], 0, 0, 0],       // clay robot
-                [cost[3], cost[4], 0, 0], // obsidian robot
-                [cost[5], 0, cost[6], 0], // geode robot
-            ]
+/// Parses blueprints like "Blueprint 1: Each ore robot costs 4 ore. Each clay
+/// robot costs 2 ore. ...", possibly spread over several lines. The resources
+/// are the ones collected by the robots, in order of appearance, and the
+/// target is geode if there's such a robot, otherwise the last one.
+pub fn parse(input: &str) -> Vec<Blueprint> {
+    let recipe_regex = Regex::new(r"Each (\w+) robot costs ([^.]+)\.").unwrap();
+    input
+        .split("Blueprint")
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(|text| {
+            let (id, recipes) = text.split_once(':').expect("missing blueprint id");
+            let recipes = recipe_regex
+                .captures_iter(recipes)
+                .map(|cap| (cap[1].to_string(), cap[2].to_string()))
+                .collect_vec();
+            let resources = recipes.iter().map(|(name, _)| name.clone()).collect_vec();
+            assert!(
+                (1..=MAX_RESOURCES).contains(&resources.len()),
+                "a blueprint must have between 1 and {MAX_RESOURCES} robot types"
+            );
+            let costs = recipes
+                .iter()
+                .map(|(_, cost)| {
+                    let mut amounts = [0; MAX_RESOURCES];
+                    for item in cost.split(" and ") {
+                        let (amount, name) = item.trim().split_once(' ').unwrap();
+                        let index = resources
+                            .iter()
+                            .position(|resource| resource == name)
+                            .unwrap_or_else(|| panic!("no robot collects {name}"));
+                        amounts[index] += amount.parse::<i64>().unwrap();
+                    }
+                    amounts
+                })
+                .collect_vec();
+            let target = resources
+                .iter()
+                .position(|resource| resource == "geode")
+                .unwrap_or(resources.len() - 1);
+            Blueprint {
+                id: id.trim().parse().unwrap(),
+                resources,
+                costs,
+                target,
+            }
         })
-        .collect_vec();
+        .collect_vec()
+}
 
-    let total_quality = sum_of_quality_levels(&blueprints);
-    println!("{total_quality}");
+pub const REPL_HELP: &str = "\
+blueprints        list the blueprints, with the cost of each robot
+geodes <id> [minutes] [strategy]
+                  max number of geodes with the given blueprint (default 24
+                  minutes). The strategy is exact (default), memo (exact, with
+                  memoization), beam or beam=<width> (approximate)
+plan <id> [minutes] [strategy]
+                  best build order, with the robots and resources after each minute
+narrative <id> [minutes] [strategy]
+                  tell the story of the best plan, minute by minute";
 
-    let geode_product = product_of_max_geodes(&blueprints[0..3]);
-    println!("{geode_product}");
+pub fn repl_command(blueprints: &mut [Blueprint], command: &str, args: &[&str]) -> CommandResult {
+    let plan = || {
+        let id: i64 = arg(args, 0, None)?;
+        let blueprint = blueprints
+            .iter()
+            .find(|blueprint| blueprint.id == id)
+            .ok_or_else(|| format!("unknown blueprint {id}"))?;
+        let minutes = arg(args, 1, Some(24))?;
+        let strategy = arg(args, 2, Some(Strategy::Exact))?;
+        Ok(best_plan(blueprint, minutes, strategy))
+    };
+    let result = match command {
+        "blueprints" => Ok(blueprints
+            .iter()
+            .map(|blueprint| {
+                let costs = (0..blueprint.num_resources()).map(|robot| {
+                    format!(
+                        "{}: {}",
+                        blueprint.resources[robot],
+                        blueprint.describe_cost(robot)
+                    )
+                });
+                format!("{}. {}", blueprint.id, costs.into_iter().join(", "))
+            })
+            .join("\n")),
+        "geodes" => plan().map(|plan| plan.geodes.to_string()),
+        "plan" => plan().map(|plan| plan.to_string()),
+        "narrative" => plan().map(|plan| plan.narrative()),
+        _ => return None,
+    };
+    Some(result)
 }
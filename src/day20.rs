@@ -55,12 +55,14 @@ fn mix(encrypted_data: &[i64], decryption_key: i64, num_rounds: i64) -> i64 {
     coordinates_sum
 }
 
-pub fn run(input: &str) {
-    let encrypted_data: Vec<i64> = input.lines().map(|line| line.parse().unwrap()).collect();
+pub fn parse(input: &str) -> Vec<i64> {
+    input.lines().map(|line| line.parse().unwrap()).collect()
+}
 
-    let coord_sum = mix(&encrypted_data, 1, 1);
-    println!("{coord_sum}");
+pub fn part1(encrypted_data: &[i64]) -> i64 {
+    mix(encrypted_data, 1, 1)
+}
 
-    let coord_sum = mix(&encrypted_data, 811589153, 10);
-    println!("{coord_sum}");
+pub fn part2(encrypted_data: &[i64]) -> i64 {
+    mix(encrypted_data, 811589153, 10)
 }
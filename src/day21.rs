@@ -1,9 +1,14 @@
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use itertools::Itertools;
-use num::{Rational64, Zero};
+use num::{BigInt, BigRational, Zero};
+use std::borrow::Cow;
+use std::{fmt, fs};
+
+use super::polynomial::{Polynomial, RationalFunction};
+use super::repl::{arg, CommandResult};
 
 #[derive(Debug, Clone)]
-enum MonkeyBusiness {
+pub enum MonkeyBusiness {
     Number(i64),
     Operation {
         left: String,
@@ -14,84 +19,855 @@ enum MonkeyBusiness {
 }
 use MonkeyBusiness::*;
 
-/// This function assumes that the provided equation simplifies to either an
-/// integer, or to a linear equation ax + b, where x is the human variable, and
-/// a and b are rational numbers. If the function runs into the = operation
-/// which happens in part2 when evaluating "root", it solves the equation for x
-/// and returns the solution as (0, solution).
-fn evaluate(monkeys: &HashMap<String, MonkeyBusiness>, name: &str) -> (Rational64, Rational64) {
-    let action = monkeys.get(name).unwrap();
-    match action {
-        Number(num) => (Rational64::new(0, 1), Rational64::new(*num, 1)),
-        Operation { left, op, right } => {
-            let (a, b) = evaluate(monkeys, left);
-            let (c, d) = evaluate(monkeys, right);
-            match *op {
-                '+' => (a + c, b + d), // (ax + b) + (cx + d) = (a + c)x + (b + d)
-                '-' => (a - c, b - d), // (ax + b) - (cx + d) = (a - c)x + (b - d)
-                '*' => {
-                    // (ax + b) * (cx + d) = acx² + (ad + bc)x + bd
-                    assert!(a * c == Rational64::zero()); // assuming no x² term
-                    ((a * d + b * c), b * d)
+/// A node of an expression, whose operands are earlier nodes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExprNode {
+    Constant(BigRational),
+    Human,
+    BinaryOp(usize, char, usize),
+}
+
+/// The expression computed by a monkey, where `Human` is the unknown x. The
+/// nodes are stored in topological order, one per monkey, so a monkey that
+/// several others wait for is a single shared node, and the expression can be
+/// built, simplified and displayed iteratively, however deep it is. This is
+/// only used to explore the monkeys in the REPL: the solver doesn't build the
+/// expression, it evaluates the monkeys directly as rational functions of x.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expr {
+    /// each node only refers to the nodes before it, and the last one is the root
+    nodes: Vec<ExprNode>,
+    /// the name of the monkey computing each node
+    names: Vec<String>,
+}
+
+impl Expr {
+    pub fn nodes(&self) -> &[ExprNode] {
+        &self.nodes
+    }
+
+    /// Folds the constant subexpressions, and removes the neutral operations,
+    /// such as x + 0 or x * 1. Divisions by zero are left untouched.
+    pub fn simplify(self) -> Expr {
+        let is = |node: &ExprNode, value: i64| {
+            *node == ExprNode::Constant(BigRational::from_integer(value.into()))
+        };
+        let mut nodes = Vec::with_capacity(self.nodes.len());
+        let mut names = Vec::with_capacity(self.nodes.len());
+        // the index of the simplified version of each node
+        let mut simplified = Vec::with_capacity(self.nodes.len());
+        for (node, name) in self.nodes.into_iter().zip(self.names) {
+            let node = match node {
+                ExprNode::BinaryOp(left, op, right) => {
+                    let (left, right) = (simplified[left], simplified[right]);
+                    match (&nodes[left], op, &nodes[right]) {
+                        (ExprNode::Constant(a), _, ExprNode::Constant(b))
+                            if op != '/' || !b.is_zero() =>
+                        {
+                            ExprNode::Constant(match op {
+                                '+' => a + b,
+                                '-' => a - b,
+                                '*' => a * b,
+                                '/' => a / b,
+                                _ => unreachable!(),
+                            })
+                        }
+                        (a, '+', _) if is(a, 0) => {
+                            simplified.push(right);
+                            continue;
+                        }
+                        (a, '*', _) if is(a, 1) => {
+                            simplified.push(right);
+                            continue;
+                        }
+                        (_, '+' | '-', b) if is(b, 0) => {
+                            simplified.push(left);
+                            continue;
+                        }
+                        (_, '*' | '/', b) if is(b, 1) => {
+                            simplified.push(left);
+                            continue;
+                        }
+                        _ => ExprNode::BinaryOp(left, op, right),
+                    }
+                }
+                node => node,
+            };
+            simplified.push(nodes.len());
+            nodes.push(node);
+            names.push(name);
+        }
+        // the folded operands are no longer needed, and the root may now be
+        // one of the nodes before the last one
+        let root = *simplified.last().unwrap();
+        Expr { nodes, names }.pruned(root)
+    }
+
+    /// Keeps only the given root and the nodes it depends on
+    fn pruned(self, root: usize) -> Expr {
+        let mut needed = vec![false; root + 1];
+        needed[root] = true;
+        for index in (0..=root).rev() {
+            if let (true, ExprNode::BinaryOp(left, _, right)) = (needed[index], &self.nodes[index])
+            {
+                needed[*left] = true;
+                needed[*right] = true;
+            }
+        }
+        let mut new_indices = vec![0; root + 1];
+        let mut nodes = vec![];
+        let mut names = vec![];
+        for ((index, node), name) in self.nodes.into_iter().enumerate().zip(self.names) {
+            if index > root || !needed[index] {
+                continue;
+            }
+            new_indices[index] = nodes.len();
+            nodes.push(match node {
+                ExprNode::BinaryOp(left, op, right) => {
+                    ExprNode::BinaryOp(new_indices[left], op, new_indices[right])
+                }
+                node => node,
+            });
+            names.push(name);
+        }
+        Expr { nodes, names }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rendered = render_dag(
+            self.nodes.len() - 1,
+            |index| match &self.nodes[index] {
+                ExprNode::Constant(value) => RenderNode::Leaf(value.to_string()),
+                ExprNode::Human => RenderNode::Leaf("x".to_string()),
+                ExprNode::BinaryOp(left, op, right) => RenderNode::Operation(*left, *op, *right),
+            },
+            |index| self.names[index].clone(),
+            |_, op| op.to_string(),
+            |_, _, _| true,
+            true,
+        );
+        write!(f, "{rendered}")
+    }
+}
+
+/// Builds the expression computed by the given monkey
+pub fn build_expr(monkeys: &HashMap<String, MonkeyBusiness>, name: &str) -> Expr {
+    let order = topological_order(monkeys, name);
+    let indices: HashMap<&str, usize> = order
+        .iter()
+        .enumerate()
+        .map(|(i, &name)| (name, i))
+        .collect();
+    let nodes = order
+        .iter()
+        .map(|&name| match &monkeys[name] {
+            Number(num) => ExprNode::Constant(BigRational::from_integer((*num).into())),
+            Operation { left, op, right } => {
+                ExprNode::BinaryOp(indices[left.as_str()], *op, indices[right.as_str()])
+            }
+            HumanVariable => ExprNode::Human,
+        })
+        .collect();
+    let names = order.iter().map(|name| name.to_string()).collect();
+    Expr { nodes, names }
+}
+
+/// A node of an expression, as seen by `render_dag`
+enum RenderNode {
+    Leaf(String),
+    Operation(usize, char, usize),
+}
+
+/// Renders an expression in infix notation. The nodes are in topological
+/// order, and `node(index)` describes each of them. The rendering is
+/// iterative, so very deep expressions are fine. An operation that several
+/// others use is written out once, on its own line after the expression, and
+/// referred to by `name(index)` everywhere else, so shared subexpressions are
+/// never expanded. `needs_parentheses(op, operand_op, is_right)` tells whether
+/// an operand that is an operation must be put in parentheses (and if
+/// `outer_parentheses` is true, so are the whole expression and the shared
+/// operations), and `operator(index, op)` renders each operator.
+fn render_dag(
+    root: usize,
+    node: impl Fn(usize) -> RenderNode,
+    name: impl Fn(usize) -> String,
+    operator: impl Fn(usize, char) -> String,
+    needs_parentheses: impl Fn(char, char, bool) -> bool,
+    outer_parentheses: bool,
+) -> String {
+    // parents come after their operands, so going backwards, the number of
+    // uses of each node is final by the time we get to it
+    let mut uses = vec![0; root + 1];
+    uses[root] = 1;
+    let mut shared = vec![false; root + 1];
+    for index in (0..=root).rev() {
+        if uses[index] == 0 {
+            continue;
+        }
+        if let RenderNode::Operation(left, _, right) = node(index) {
+            shared[index] = uses[index] > 1;
+            uses[left] += 1;
+            uses[right] += 1;
+        }
+    }
+    enum Token {
+        Node(usize),
+        Text(String),
+    }
+    let mut rendered = String::new();
+    let definitions = (0..root).rev().filter(|&index| shared[index]);
+    for definition in std::iter::once(root).chain(definitions) {
+        if definition != root {
+            rendered += &format!("\n{} = ", name(definition));
+        }
+        let mut stack = vec![Token::Node(definition)];
+        if outer_parentheses && matches!(node(definition), RenderNode::Operation(..)) {
+            stack = vec![
+                Token::Text(")".to_string()),
+                Token::Node(definition),
+                Token::Text("(".to_string()),
+            ];
+        }
+        while let Some(token) = stack.pop() {
+            let index = match token {
+                Token::Text(text) => {
+                    rendered += &text;
+                    continue;
                 }
-                '/' => {
-                    // (ax + b) / (cx + d) = a/d x + b/d  assuming c is 0
-                    assert!(c == Rational64::zero());
-                    (a / d, b / d)
+                Token::Node(index) if shared[index] && index != definition => {
+                    rendered += &name(index);
+                    continue;
                 }
-                '=' => {
-                    // solve ax + b = cx + d => x = (d - b) / (a - c) assuming a ≠ c
-                    assert!(a != c);
-                    let solution = (d - b) / (a - c);
-                    assert!(*solution.denom() == 1); // assuming the solution is an integer
-                    (Rational64::zero(), solution)
+                Token::Node(index) => index,
+            };
+            let (left, op, right) = match node(index) {
+                RenderNode::Leaf(text) => {
+                    rendered += &text;
+                    continue;
+                }
+                RenderNode::Operation(left, op, right) => (left, op, right),
+            };
+            let parenthesize = |operand: usize, is_right: bool| match node(operand) {
+                RenderNode::Operation(_, operand_op, _) if !shared[operand] => {
+                    needs_parentheses(op, operand_op, is_right)
+                }
+                _ => false,
+            };
+            // pushed in reverse order
+            for (operand, is_right) in [(right, true), (left, false)] {
+                let parentheses = parenthesize(operand, is_right);
+                if parentheses {
+                    stack.push(Token::Text(")".to_string()));
+                }
+                stack.push(Token::Node(operand));
+                if parentheses {
+                    stack.push(Token::Text("(".to_string()));
+                }
+                if is_right {
+                    stack.push(Token::Text(format!(" {} ", operator(index, op))));
+                }
+            }
+        }
+    }
+    rendered
+}
+
+/// Returns the given monkey and all the monkeys it directly or indirectly
+/// waits for, each listed once, and ordered so that every monkey comes after
+/// the monkeys it waits for. The given monkey is therefore last. This is done
+/// iteratively, so arbitrarily long chains of monkeys are fine.
+pub fn topological_order<'a>(
+    monkeys: &'a HashMap<String, MonkeyBusiness>,
+    name: &'a str,
+) -> Vec<&'a str> {
+    let mut order = vec![];
+    let mut visited = HashSet::new();
+    // true means that the operands were already pushed on the stack
+    let mut stack = vec![(name, false)];
+    while let Some((name, expanded)) = stack.pop() {
+        if expanded {
+            order.push(name);
+            continue;
+        }
+        if !visited.insert(name) {
+            continue;
+        }
+        stack.push((name, true));
+        let action = monkeys
+            .get(name)
+            .unwrap_or_else(|| panic!("unknown monkey {name}"));
+        if let Operation { left, right, .. } = action {
+            stack.push((right, false));
+            stack.push((left, false));
+        }
+    }
+    order
+}
+
+/// Evaluates the monkeys in the given topological order, each exactly once,
+/// using 64-bit integers. Returns `None` as soon as an operation overflows or
+/// does not give an integer, or if a monkey depends on the human variable.
+fn evaluate_checked(monkeys: &HashMap<String, MonkeyBusiness>, order: &[&str]) -> Option<i64> {
+    let mut values: HashMap<&str, i64> = HashMap::with_capacity(order.len());
+    for &name in order {
+        let value = match &monkeys[name] {
+            Number(num) => *num,
+            Operation { left, op, right } => {
+                let (a, b) = (values[left.as_str()], values[right.as_str()]);
+                match op {
+                    '+' => a.checked_add(b)?,
+                    '-' => a.checked_sub(b)?,
+                    '*' => a.checked_mul(b)?,
+                    '/' if a.checked_rem(b)? == 0 => a.checked_div(b)?,
+                    '/' => return None,
+                    _ => unreachable!(),
                 }
-                _ => unreachable!(),
             }
+            HumanVariable => return None,
+        };
+        values.insert(name, value);
+    }
+    values.get(order.last()?).copied()
+}
+
+/// The value of a monkey while evaluating. Integers are by far the most common
+/// values, and they are much cheaper to compute with than rational functions.
+#[derive(Debug, Clone)]
+enum Value {
+    Integer(BigInt),
+    Function(RationalFunction),
+}
+
+impl Value {
+    fn into_function(self) -> RationalFunction {
+        match self {
+            Value::Integer(value) => RationalFunction::from_polynomial(Polynomial::constant(
+                BigRational::from_integer(value),
+            )),
+            Value::Function(function) => function,
         }
-        HumanVariable => (Rational64::new(1, 1), Rational64::zero()), // x = 1 * x + 0
     }
+
+    fn as_function(&self) -> Cow<'_, RationalFunction> {
+        match self {
+            Value::Integer(_) => Cow::Owned(self.clone().into_function()),
+            Value::Function(function) => Cow::Borrowed(function),
+        }
+    }
+}
+
+/// Evaluates the monkeys in the given topological order, each exactly once,
+/// as integers when possible, or else as rational functions of the human
+/// variable x, and returns the values of the requested monkeys. Values are
+/// dropped as soon as they're no longer needed, to save memory. Also returns
+/// the numerators of all the divisors that depend on x, since x must not be
+/// one of their roots.
+fn evaluate_all(
+    monkeys: &HashMap<String, MonkeyBusiness>,
+    order: &[&str],
+    requested: &[&str],
+) -> (Vec<Value>, Vec<Polynomial>) {
+    let indices: HashMap<&str, usize> = order
+        .iter()
+        .enumerate()
+        .map(|(i, &name)| (name, i))
+        .collect();
+    let jobs = order
+        .iter()
+        .map(|&name| match &monkeys[name] {
+            Operation { left, op, right } => {
+                Some((indices[left.as_str()], *op, indices[right.as_str()]))
+            }
+            _ => None,
+        })
+        .collect_vec();
+    let mut remaining_uses = vec![0; order.len()];
+    for &(left, _, right) in jobs.iter().flatten() {
+        remaining_uses[left] += 1;
+        remaining_uses[right] += 1;
+    }
+    for name in requested {
+        remaining_uses[indices[name]] += 1;
+    }
+    let mut values: Vec<Option<Value>> = vec![None; order.len()];
+    let mut divisors = vec![];
+    for (index, &name) in order.iter().enumerate() {
+        let value = match (&monkeys[name], jobs[index]) {
+            (Number(num), _) => Value::Integer((*num).into()),
+            (HumanVariable, _) => {
+                Value::Function(RationalFunction::from_polynomial(Polynomial::x()))
+            }
+            (Operation { .. }, Some((left, op, right))) => {
+                let (a, b) = (
+                    values[left].as_ref().unwrap(),
+                    values[right].as_ref().unwrap(),
+                );
+                let value = match (op, a, b) {
+                    ('+', Value::Integer(a), Value::Integer(b)) => Value::Integer(a + b),
+                    ('-', Value::Integer(a), Value::Integer(b)) => Value::Integer(a - b),
+                    ('*', Value::Integer(a), Value::Integer(b)) => Value::Integer(a * b),
+                    ('/', Value::Integer(a), Value::Integer(b))
+                        if !b.is_zero() && (a % b).is_zero() =>
+                    {
+                        Value::Integer(a / b)
+                    }
+                    (op, a, b) => {
+                        let (a, b) = (a.as_function(), b.as_function());
+                        Value::Function(match op {
+                            '+' => a.add(&b),
+                            '-' => a.sub(&b),
+                            '*' => a.mul(&b),
+                            '/' => {
+                                if b.as_constant().is_none() {
+                                    divisors.push(b.numerator.clone());
+                                }
+                                a.div(&b)
+                            }
+                            _ => unreachable!(),
+                        })
+                    }
+                };
+                for operand in [left, right] {
+                    remaining_uses[operand] -= 1;
+                    if remaining_uses[operand] == 0 {
+                        values[operand] = None;
+                    }
+                }
+                value
+            }
+            (Operation { .. }, None) => unreachable!(),
+        };
+        values[index] = Some(value);
+    }
+    let requested = requested
+        .iter()
+        .map(|name| values[indices[name]].clone().unwrap());
+    (requested.collect(), divisors)
 }
 
-pub fn run(input: &str) {
-    let mut monkeys: HashMap<String, MonkeyBusiness> = input
+/// Evaluates the given monkey, as a rational function of the human variable x
+/// (which is just a constant if the monkey does not depend on x).
+pub fn evaluate(monkeys: &HashMap<String, MonkeyBusiness>, name: &str) -> RationalFunction {
+    let order = topological_order(monkeys, name);
+    let (values, _) = evaluate_all(monkeys, &order, &[name]);
+    values.into_iter().next().unwrap().into_function()
+}
+
+/// The values of x for which both sides of an equation are equal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Solution {
+    NoSolution,
+    Roots(Vec<BigInt>),
+    InfinitelyMany,
+    /// The equation is a polynomial of degree 3 or more whose integer roots
+    /// are out of reach, since its constant term is too large to factorize
+    CannotSolve {
+        degree: usize,
+    },
+}
+
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Solution::NoSolution => write!(f, "no solution"),
+            Solution::Roots(roots) => write!(f, "{}", roots.iter().join(", ")),
+            Solution::InfinitelyMany => write!(f, "infinitely many solutions"),
+            Solution::CannotSolve { degree } => write!(
+                f,
+                "cannot solve: the equation has degree {degree}, and its constant term \
+                 is too large to factorize"
+            ),
+        }
+    }
+}
+
+/// Finds every integer x such that left = right, excluding the roots of the
+/// divisors, for which one of the sides would divide by zero.
+pub fn solve_equation(
+    left: &RationalFunction,
+    right: &RationalFunction,
+    divisors: &[Polynomial],
+) -> Solution {
+    // p/q = r/s <=> ps - rq = 0, as long as q and s are nonzero
+    let difference = left
+        .numerator
+        .mul(&right.denominator)
+        .sub(&right.numerator.mul(&left.denominator));
+    if difference.is_zero() {
+        return Solution::InfinitelyMany;
+    }
+    let Some(roots) = difference.integer_roots() else {
+        return Solution::CannotSolve {
+            degree: difference.degree().unwrap(),
+        };
+    };
+    let roots = roots
+        .into_iter()
+        .filter(|root| {
+            let x = BigRational::from_integer(root.clone());
+            divisors.iter().all(|divisor| !divisor.eval(&x).is_zero())
+        })
+        .collect_vec();
+    if roots.is_empty() {
+        Solution::NoSolution
+    } else {
+        Solution::Roots(roots)
+    }
+}
+
+/// A problem in the puzzle input, which prevents evaluating the monkeys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MonkeyError {
+    InvalidLine(String),
+    UndefinedMonkey { monkey: String, name: String },
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for MonkeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MonkeyError::InvalidLine(line) => write!(f, "invalid line: {line:?}"),
+            MonkeyError::UndefinedMonkey { monkey, name } => {
+                write!(f, "monkey {monkey} waits for undefined monkey {name}")
+            }
+            MonkeyError::Cycle(names) => write!(f, "cycle: {}", names.join(" -> ")),
+        }
+    }
+}
+
+/// Checks that every monkey only waits for defined monkeys, and that no
+/// monkey ends up waiting for itself. The graph is explored iteratively, so
+/// very long chains of monkeys are fine.
+pub fn validate(monkeys: &HashMap<String, MonkeyBusiness>) -> Result<(), MonkeyError> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        InProgress,
+        Done,
+    }
+    let mut states: HashMap<&str, State> = HashMap::new();
+    for start in monkeys.keys() {
+        if states.contains_key(start.as_str()) {
+            continue;
+        }
+        // the stack holds the current path, along with the number of
+        // operands of each monkey that were already explored
+        let mut stack: Vec<(&str, usize)> = vec![(start, 0)];
+        states.insert(start, State::InProgress);
+        while let Some((name, explored)) = stack.last_mut() {
+            let operand = match (&monkeys[*name], *explored) {
+                (Operation { left, .. }, 0) => left,
+                (Operation { right, .. }, 1) => right,
+                _ => {
+                    states.insert(name, State::Done);
+                    stack.pop();
+                    continue;
+                }
+            };
+            *explored += 1;
+            let Some((operand, _)) = monkeys.get_key_value(operand.as_str()) else {
+                return Err(MonkeyError::UndefinedMonkey {
+                    monkey: name.to_string(),
+                    name: operand.to_string(),
+                });
+            };
+            match states.get(operand.as_str()) {
+                Some(State::Done) => {}
+                Some(State::InProgress) => {
+                    let mut cycle = stack
+                        .iter()
+                        .map(|(name, _)| name.to_string())
+                        .skip_while(|name| name != operand)
+                        .collect_vec();
+                    cycle.push(operand.to_string());
+                    return Err(MonkeyError::Cycle(cycle));
+                }
+                None => {
+                    states.insert(operand, State::InProgress);
+                    stack.push((operand, 0));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parses and validates the monkeys
+pub fn try_parse(input: &str) -> Result<HashMap<String, MonkeyBusiness>, MonkeyError> {
+    let monkeys = input
         .lines()
         .map(|line| {
-            let (name, action) = line.split(": ").collect_tuple().unwrap();
+            let invalid = || MonkeyError::InvalidLine(line.to_string());
+            let (name, action) = line.split(": ").collect_tuple().ok_or_else(invalid)?;
             let name = name.to_string();
             if let Ok(num) = action.parse::<i64>() {
-                (name, Number(num))
-            } else {
-                let mut parts = action.split(' ');
-                (
-                    name,
-                    Operation {
-                        left: parts.next().unwrap().to_string(),
-                        op: parts.next().unwrap().chars().next().unwrap(),
-                        right: parts.next().unwrap().to_string(),
-                    },
-                )
+                return Ok((name, Number(num)));
             }
+            let (left, op, right) = action.split(' ').collect_tuple().ok_or_else(invalid)?;
+            if !["+", "-", "*", "/"].contains(&op) {
+                return Err(invalid());
+            }
+            Ok((
+                name,
+                Operation {
+                    left: left.to_string(),
+                    op: op.chars().next().unwrap(),
+                    right: right.to_string(),
+                },
+            ))
         })
-        .collect();
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    validate(&monkeys)?;
+    Ok(monkeys)
+}
 
-    let (_, result) = evaluate(&monkeys, "root");
-    println!("{}", result.to_integer());
+pub fn parse(input: &str) -> HashMap<String, MonkeyBusiness> {
+    try_parse(input).unwrap_or_else(|error| panic!("{error}"))
+}
 
-    // make the tweaks for part 2
+/// Uses 64-bit integers, unless they overflow, in which case it falls back to
+/// big integers (or exact rational arithmetic if a division is not exact).
+pub fn part1(monkeys: &HashMap<String, MonkeyBusiness>) -> BigInt {
+    let order = topological_order(monkeys, "root");
+    if let Some(result) = evaluate_checked(monkeys, &order) {
+        return result.into();
+    }
+    let (values, _) = evaluate_all(monkeys, &order, &["root"]);
+    match values.into_iter().next().unwrap() {
+        Value::Integer(result) => result,
+        Value::Function(function) => {
+            let result = function
+                .as_constant()
+                .expect("root should not depend on humn");
+            assert!(result.is_integer(), "root should yell an integer");
+            result.to_integer()
+        }
+    }
+}
+
+pub fn part2(monkeys: &HashMap<String, MonkeyBusiness>) -> Solution {
+    let mut monkeys = monkeys.clone();
     monkeys.insert("humn".to_string(), HumanVariable);
-    let root = monkeys.get_mut(&"root".to_string()).unwrap();
-    if let Operation {
-        left: _,
-        op,
-        right: _,
-    } = root
-    {
-        *op = '=';
-    } else {
+    let Some(Operation { left, right, .. }) = monkeys.get("root") else {
         panic!("root should be an Operation");
+    };
+    let mut order = topological_order(&monkeys, "root");
+    order.pop(); // root's own operation is replaced with =
+    let (values, divisors) = evaluate_all(&monkeys, &order, &[left, right]);
+    let (left, right) = values
+        .into_iter()
+        .map(Value::into_function)
+        .collect_tuple()
+        .unwrap();
+    solve_equation(&left, &right, &divisors)
+}
+
+fn precedence(op: char) -> u8 {
+    match op {
+        '=' => 0,
+        '+' | '-' => 1,
+        _ => 2,
+    }
+}
+
+const HIGHLIGHT: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+
+/// Returns the names of the monkeys whose result depends on humn
+fn human_branch(monkeys: &HashMap<String, MonkeyBusiness>) -> HashSet<&str> {
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (name, action) in monkeys {
+        if let Operation { left, right, .. } = action {
+            dependents.entry(left).or_default().push(name);
+            dependents.entry(right).or_default().push(name);
+        }
     }
+    let mut branch = HashSet::new();
+    let mut stack = vec!["humn"];
+    while let Some(name) = stack.pop() {
+        if branch.insert(name) {
+            stack.extend(dependents.get(name).into_iter().flatten());
+        }
+    }
+    branch
+}
 
-    let (_, result) = evaluate(&monkeys, "root");
-    println!("{}", result);
+/// Renders the expression computed by the given monkey in infix notation,
+/// with as few parentheses as possible. If `highlight` is true, the operations
+/// that depend on humn are highlighted using ANSI escape codes. For root, the
+/// operation is displayed as = since that's what it means in part 2. A monkey
+/// that several others wait for is shown once, on its own line.
+pub fn render_expression(
+    monkeys: &HashMap<String, MonkeyBusiness>,
+    name: &str,
+    highlight: bool,
+) -> String {
+    let branch = human_branch(monkeys);
+    let order = topological_order(monkeys, name);
+    let indices: HashMap<&str, usize> = order
+        .iter()
+        .enumerate()
+        .map(|(i, &name)| (name, i))
+        .collect();
+    let paint = |name: &str, text: String| match highlight && branch.contains(name) {
+        true => format!("{HIGHLIGHT}{text}{RESET}"),
+        false => text,
+    };
+    render_dag(
+        order.len() - 1,
+        |index| match &monkeys[order[index]] {
+            _ if order[index] == "humn" => RenderNode::Leaf(paint("humn", "humn".to_string())),
+            Number(num) => RenderNode::Leaf(num.to_string()),
+            HumanVariable => RenderNode::Leaf(paint(order[index], order[index].to_string())),
+            Operation { left, op, right } => RenderNode::Operation(
+                indices[left.as_str()],
+                if order[index] == "root" { '=' } else { *op },
+                indices[right.as_str()],
+            ),
+        },
+        |index| paint(order[index], order[index].to_string()),
+        |index, op| match op {
+            '=' => op.to_string(),
+            _ => paint(order[index], op.to_string()),
+        },
+        // a - (b + c) and a / (b * c) need parentheses, a + (b - c) doesn't
+        |op, operand_op, is_right| {
+            precedence(operand_op) < precedence(op)
+                || (is_right && precedence(operand_op) == precedence(op) && matches!(op, '-' | '/'))
+        },
+        false,
+    )
+}
+
+/// Exports the dependency graph of the monkeys in the Graphviz DOT format,
+/// with an edge from each monkey to the monkeys it waits for. The monkeys that
+/// depend on humn are shown in red.
+pub fn to_dot(monkeys: &HashMap<String, MonkeyBusiness>) -> String {
+    let branch = human_branch(monkeys);
+    let mut dot = "digraph monkeys {\n".to_string();
+    for (name, action) in monkeys.iter().sorted_by_key(|(name, _)| *name) {
+        let label = match action {
+            _ if name == "humn" => "humn".to_string(),
+            Number(num) => format!("{name}\\n{num}"),
+            Operation { op, .. } => format!("{name}\\n{op}"),
+            HumanVariable => format!("{name}\\nx"),
+        };
+        let color = match branch.contains(name.as_str()) {
+            true => ", color=red, fontcolor=red",
+            false => "",
+        };
+        dot += &format!("  {name} [label=\"{label}\"{color}];\n");
+        if let Operation { left, right, .. } = action {
+            for (operand, side) in [(left, "L"), (right, "R")] {
+                let color = match branch.contains(operand.as_str()) {
+                    true => ", color=red",
+                    false => "",
+                };
+                dot += &format!("  {name} -> {operand} [label=\"{side}\"{color}];\n");
+            }
+        }
+    }
+    dot += "}\n";
+    dot
+}
+
+pub const REPL_HELP: &str = "\
+show <name>       show a monkey's job
+tree <name> [depth]
+                  show the expression computed by a monkey (default depth 3)
+simplify <name>   show the simplified expression computed by a monkey
+eval <name>       evaluate a monkey, as a rational function of x if it depends on x
+set <name> <num>  make a monkey yell the given number, or the unknown x if num is x
+equation [name]   show the equation formed by the monkeys, or the expression
+                  computed by the given monkey, highlighting what depends on humn
+dot [path]        export the dependency graph in Graphviz DOT format
+part1 / part2     solve the riddle with the current monkeys";
+
+/// Renders the expression computed by a monkey, expanding at most `depth`
+/// levels of operations. Since the depth is up to the user, this is done
+/// iteratively, like `topological_order`.
+fn expression_tree<'a>(
+    monkeys: &'a HashMap<String, MonkeyBusiness>,
+    name: &'a str,
+    depth: usize,
+) -> String {
+    enum Token<'a> {
+        Monkey(&'a str, usize),
+        Text(String),
+    }
+    let mut rendered = String::new();
+    let mut stack = vec![Token::Monkey(name, depth)];
+    while let Some(token) = stack.pop() {
+        let (name, depth) = match token {
+            Token::Text(text) => {
+                rendered += &text;
+                continue;
+            }
+            Token::Monkey(name, depth) => (name, depth),
+        };
+        match monkeys.get(name) {
+            Some(Number(num)) => rendered += &num.to_string(),
+            Some(HumanVariable) => rendered += "x",
+            Some(Operation { left, op, right }) if depth > 0 => {
+                rendered += "(";
+                // pushed in reverse order
+                stack.extend([
+                    Token::Text(")".to_string()),
+                    Token::Monkey(right, depth - 1),
+                    Token::Text(format!(" {op} ")),
+                    Token::Monkey(left, depth - 1),
+                ]);
+            }
+            _ => rendered += name,
+        }
+    }
+    rendered
+}
+
+pub fn repl_command(
+    monkeys: &mut HashMap<String, MonkeyBusiness>,
+    command: &str,
+    args: &[&str],
+) -> CommandResult {
+    let name: Result<String, String> = arg(args, 0, None);
+    let name = name.and_then(|name| {
+        if monkeys.contains_key(&name) {
+            Ok(name)
+        } else {
+            Err(format!("unknown monkey {name}"))
+        }
+    });
+    let result = match command {
+        "show" => name.map(|name| format!("{:?}", monkeys[&name])),
+        "tree" => name.and_then(|name| {
+            let depth = arg(args, 1, Some(3))?;
+            Ok(expression_tree(monkeys, &name, depth))
+        }),
+        "simplify" => name.map(|name| build_expr(monkeys, &name).simplify().to_string()),
+        "eval" => name.map(|name| evaluate(monkeys, &name).to_string()),
+        "set" => name.and_then(|name| {
+            if args.get(1) == Some(&"x") {
+                monkeys.insert(name, HumanVariable);
+                return Ok("x".to_string());
+            }
+            let num = arg(args, 1, None)?;
+            monkeys.insert(name, Number(num));
+            Ok(format!("{num}"))
+        }),
+        "equation" => {
+            let name = args.first().map_or(Ok("root".to_string()), |_| name);
+            name.map(|name| render_expression(monkeys, &name, true))
+        }
+        "dot" => match args.first() {
+            Some(path) => fs::write(path, to_dot(monkeys))
+                .map(|_| format!("saved to {path}"))
+                .map_err(|error| error.to_string()),
+            None => Ok(to_dot(monkeys)),
+        },
+        "part1" => Ok(part1(monkeys).to_string()),
+        "part2" => Ok(part2(monkeys).to_string()),
+        _ => return None,
+    };
+    Some(result)
 }
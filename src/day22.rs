@@ -1,8 +1,10 @@
 use itertools::Itertools;
 use regex::Regex;
 
+use super::repl::{arg, CommandResult};
+
 #[derive(Debug, Copy, Clone)]
-enum Action {
+pub enum Action {
     MoveForward(i32),
     TurnLeft,
     TurnRight,
@@ -21,67 +23,209 @@ fn offboard(board: &[Vec<u8>], x: i32, y: i32) -> bool {
     x >= row.len() || row[x] == b' '
 }
 
-fn get_password(board: &[Vec<u8>], actions: &[Action], is_cube: bool) -> i32 {
+/// Right, down, left, up: the index of each direction is its facing score
+const DIRECTIONS: [(i32, i32); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+type Vec3 = [i32; 3];
+
+fn neg(v: Vec3) -> Vec3 {
+    [-v[0], -v[1], -v[2]]
+}
+
+/// The orientation of a face once the net is folded into a cube: the 3D
+/// directions of its outward normal, and of the board's x and y axes.
+#[derive(Debug, Copy, Clone)]
+struct Face {
+    fx: i32, // position of the face in the net, in face units
+    fy: i32,
+    normal: Vec3,
+    right: Vec3,
+    down: Vec3,
+}
+
+impl Face {
+    /// The 3D direction corresponding to the given board direction
+    fn axis(&self, direction: usize) -> Vec3 {
+        [self.right, self.down, neg(self.right), neg(self.down)][direction]
+    }
+
+    /// The face we get to by folding the net along the edge in this direction.
+    /// For example, the face on the right of this one ends up facing right, and
+    /// its own right points inward, toward the back of this face.
+    fn fold(&self, direction: usize) -> Face {
+        let (dx, dy) = DIRECTIONS[direction];
+        let (normal, right, down) = match direction {
+            0 => (self.right, neg(self.normal), self.down),
+            1 => (self.down, self.right, neg(self.normal)),
+            2 => (neg(self.right), self.normal, self.down),
+            3 => (neg(self.down), self.right, self.normal),
+            _ => unreachable!(),
+        };
+        Face {
+            fx: self.fx + dx,
+            fy: self.fy + dy,
+            normal,
+            right,
+            down,
+        }
+    }
+}
+
+/// The board folded into a cube. This works for any of the 11 cube nets, and
+/// any face size: rather than hard-coding the wrapping rules, we fold the net
+/// by walking from face to face, keeping track of each face's orientation in
+/// 3D. Two faces share an edge if one's normal is the direction in which we
+/// leave the other, and the orientation of the edges tells us how to map the
+/// positions along the edge.
+struct Cube {
+    size: i32,
+    faces: Vec<Face>,
+}
+
+impl Cube {
+    /// Returns None if the board is not a valid cube net
+    fn new(board: &[Vec<u8>]) -> Option<Cube> {
+        let num_tiles = board.iter().flatten().filter(|&&cell| cell != b' ').count();
+        let size = ((num_tiles / 6) as f64).sqrt().round() as i32;
+        if size == 0 || 6 * size * size != num_tiles as i32 {
+            return None;
+        }
+        let (fx, fy) = (
+            board[0].iter().position(|&cell| cell != b' ')? as i32 / size,
+            0,
+        );
+        let mut faces = vec![Face {
+            fx,
+            fy,
+            normal: [0, 0, -1],
+            right: [1, 0, 0],
+            down: [0, 1, 0],
+        }];
+        let mut index = 0;
+        while index < faces.len() {
+            let face = faces[index];
+            for direction in 0..4 {
+                let neighbor = face.fold(direction);
+                let is_known = faces
+                    .iter()
+                    .any(|f| (f.fx, f.fy) == (neighbor.fx, neighbor.fy));
+                if !is_known && !offboard(board, neighbor.fx * size, neighbor.fy * size) {
+                    faces.push(neighbor);
+                }
+            }
+            index += 1;
+        }
+        let has_six_sides = faces.iter().map(|face| face.normal).unique().count() == 6;
+        (faces.len() == 6 && has_six_sides).then_some(Cube { size, faces })
+    }
+
+    /// Faces are numbered from 1 to 6 in reading order
+    fn face_number(&self, x: i32, y: i32) -> usize {
+        let face = self.face_at(x, y);
+        self.faces
+            .iter()
+            .filter(|f| (f.fy, f.fx) < (face.fy, face.fx))
+            .count()
+            + 1
+    }
+
+    fn face_at(&self, x: i32, y: i32) -> &Face {
+        let (fx, fy) = (x.div_euclid(self.size), y.div_euclid(self.size));
+        self.faces
+            .iter()
+            .find(|face| (face.fx, face.fy) == (fx, fy))
+            .unwrap()
+    }
+
+    /// Leaves the face containing (x, y) in the given direction, and returns
+    /// the new position and direction on the neighboring face.
+    fn wrap(&self, x: i32, y: i32, direction: usize) -> (i32, i32, usize) {
+        let last = self.size - 1;
+        let from = self.face_at(x, y);
+        let (local_x, local_y) = (x - from.fx * self.size, y - from.fy * self.size);
+        let to = self
+            .faces
+            .iter()
+            .find(|face| face.normal == from.axis(direction))
+            .unwrap();
+        let entry_edge = (0..4).find(|&edge| to.axis(edge) == from.normal).unwrap();
+        let new_direction = (entry_edge + 2) % 4;
+        // position along the edge, following the other axis of each face
+        let (along_from, offset) = match direction {
+            0 | 2 => (from.down, local_y),
+            _ => (from.right, local_x),
+        };
+        let along_to = if entry_edge % 2 == 0 {
+            to.down
+        } else {
+            to.right
+        };
+        let offset = if along_to == along_from {
+            offset
+        } else {
+            last - offset
+        };
+        let (new_x, new_y) = match entry_edge {
+            0 => (last, offset),
+            1 => (offset, last),
+            2 => (0, offset),
+            3 => (offset, 0),
+            _ => unreachable!(),
+        };
+        (
+            to.fx * self.size + new_x,
+            to.fy * self.size + new_y,
+            new_direction,
+        )
+    }
+}
+
+const EDGE_NAMES: [&str; 4] = ["right", "bottom", "left", "top"];
+
+/// Where the path went over an edge of the board, and where it came back
+#[derive(Debug, Copy, Clone)]
+struct Wrap {
+    from: (i32, i32, usize),
+    to: (i32, i32, usize),
+    is_blocked: bool, // true if there was a wall on the other side
+}
+
+/// Every cell visited along the path, with the direction we were facing when
+/// we left it (or when we stopped), plus every wrap transition.
+#[derive(Default)]
+pub struct Trace {
+    visited: Vec<(i32, i32, usize)>,
+    wraps: Vec<Wrap>,
+}
+
+fn walk(
+    board: &[Vec<u8>],
+    actions: &[Action],
+    is_cube: bool,
+    mut trace: Option<&mut Trace>,
+) -> i32 {
+    let cube = is_cube.then(|| Cube::new(board).expect("the board is not a cube net"));
     let start_x = board[0].iter().position(|&cell| cell == b'.').unwrap() as i32;
     let (mut x, mut y) = (start_x, 0);
-    let (mut dx, mut dy) = (1, 0);
+    let mut direction = 0;
     for action in actions {
         match action {
             TurnLeft => {
-                (dx, dy) = (dy, -dx);
+                direction = (direction + 3) % 4;
             }
             TurnRight => {
-                (dx, dy) = (-dy, dx);
+                direction = (direction + 1) % 4;
             }
             MoveForward(steps) => {
                 for _ in 0..*steps {
-                    let (mut nx, mut ny, mut ndx, mut ndy) = (x + dx, y + dy, dx, dy);
+                    if let Some(trace) = trace.as_mut() {
+                        trace.visited.push((x, y, direction));
+                    }
+                    let (dx, dy) = DIRECTIONS[direction];
+                    let (mut nx, mut ny, mut new_direction) = (x + dx, y + dy, direction);
                     if offboard(board, nx, ny) {
-                        if is_cube {
-                            // This is the quick & dirty manual solution to the cube problem.
-                            // It will only work if you have the same board shape as mine.
-                            // Finding the 14 transition rules manually was the fastest
-                            // way for me to solve this problem, but I'll probably revisit
-                            // this later to find a more general & elegant solution.
-                            // The 14 rules below correspond to the following movements:
-                            //
-                            //                         1234
-                            //                         ↑↓↑↓
-                            //                       5←FFRR→7
-                            //                       6→FFRR←8
-                            //                       9↙DD↙↑11
-                            //                     10↓↗DD↗12
-                            //                     6←LLBB→8
-                            //                     5→LLBB←7
-                            //                     1→UU↙↑13
-                            //                     2←UU↗14
-                            //                       ↑↓
-                            //                       34
-                            //
-                            // F=Front, B=Back, R=Right, L=Left, D=Down, U=Up
-                            (nx, ny, ndx, ndy) = match (nx, ny, dx, dy) {
-                                (nx, -1, _, _) if (50..100).contains(&nx) => (0, 100 + nx, 1, 0), //  1
-                                (-1, ny, _, _) if (150..200).contains(&ny) => (ny - 100, 0, 0, 1), //  2
-                                (nx, -1, _, _) if (100..150).contains(&nx) => {
-                                    (nx - 100, 199, 0, -1)
-                                } //  3
-                                (nx, 200, _, _) if (0..50).contains(&nx) => (nx + 100, 0, 0, 1), //  4
-                                (49, ny, _, _) if (0..50).contains(&ny) => (0, 149 - ny, 1, 0), //  5
-                                (-1, ny, _, _) if (100..150).contains(&ny) => (50, 149 - ny, 1, 0), //  6
-                                (150, ny, _, _) if (0..50).contains(&ny) => (99, 149 - ny, -1, 0), //  7
-                                (100, ny, _, _) if (100..150).contains(&ny) => {
-                                    (149, 149 - ny, -1, 0)
-                                } //  8
-                                (49, ny, -1, 0) if (50..100).contains(&ny) => (ny - 50, 100, 0, 1), //  9
-                                (nx, 99, 0, -1) if (0..50).contains(&nx) => (50, 50 + nx, 1, 0), // 10
-                                (nx, 50, 0, 1) if (100..150).contains(&nx) => (99, nx - 50, -1, 0), // 11
-                                (100, ny, 1, 0) if (50..100).contains(&ny) => (50 + ny, 49, 0, -1), // 12
-                                (nx, 150, 0, 1) if (50..100).contains(&nx) => (49, nx + 100, -1, 0), // 13
-                                (50, ny, 1, 0) if (150..200).contains(&ny) => {
-                                    (ny - 100, 149, 0, -1)
-                                } // 14
-                                _ => (nx, ny, ndx, ndy),
-                            }
+                        if let Some(cube) = &cube {
+                            (nx, ny, new_direction) = cube.wrap(x, y, direction);
                         } else {
                             loop {
                                 (nx, ny) = (nx - dx, ny - dy);
@@ -91,23 +235,88 @@ fn get_password(board: &[Vec<u8>], actions: &[Action], is_cube: bool) -> i32 {
                                 }
                             }
                         }
+                        if let Some(trace) = trace.as_mut() {
+                            trace.wraps.push(Wrap {
+                                from: (x, y, direction),
+                                to: (nx, ny, new_direction),
+                                is_blocked: board[ny as usize][nx as usize] == b'#',
+                            });
+                        }
                     }
                     if board[ny as usize][nx as usize] == b'#' {
                         break;
                     }
-                    (x, y, dx, dy) = (nx, ny, ndx, ndy);
+                    (x, y, direction) = (nx, ny, new_direction);
                 }
             }
         }
     }
-    let facing = [(1, 0), (0, 1), (-1, 0), (0, -1)]
+    if let Some(trace) = trace {
+        trace.visited.push((x, y, direction));
+    }
+    1000 * (y + 1) + 4 * (x + 1) + direction as i32
+}
+
+fn get_password(board: &[Vec<u8>], actions: &[Action], is_cube: bool) -> i32 {
+    walk(board, actions, is_cube, None)
+}
+
+/// Follows the path like `get_password()`, and also returns the trace.
+pub fn trace_path(board: &[Vec<u8>], actions: &[Action], is_cube: bool) -> (i32, Trace) {
+    let mut trace = Trace::default();
+    let password = walk(board, actions, is_cube, Some(&mut trace));
+    (password, trace)
+}
+
+/// Draws the path over the board, like in the puzzle statement
+pub fn render_trace(board: &[Vec<u8>], trace: &Trace) -> String {
+    let mut board = board.to_vec();
+    for &(x, y, direction) in &trace.visited {
+        board[y as usize][x as usize] = b">v<^"[direction];
+    }
+    board
+        .iter()
+        .map(|row| String::from_utf8_lossy(row))
+        .join("\n")
+}
+
+/// Describes each wrap transition. If the board is a cube net, the faces are
+/// numbered 1 to 6 in reading order, even when the board is not folded.
+pub fn describe_wraps(board: &[Vec<u8>], trace: &Trace) -> String {
+    let cube = Cube::new(board);
+    let describe = |(x, y, direction): (i32, i32, usize), edge: usize| {
+        let position = format!(
+            "({}, {}) facing {}",
+            x + 1,
+            y + 1,
+            b">v<^"[direction] as char
+        );
+        match &cube {
+            Some(cube) => format!(
+                "face {} {} edge {position}",
+                cube.face_number(x, y),
+                EDGE_NAMES[edge]
+            ),
+            None => format!("{} edge {position}", EDGE_NAMES[edge]),
+        }
+    };
+    trace
+        .wraps
         .iter()
-        .position(|&pos| pos == (dx, dy))
-        .unwrap() as i32;
-    1000 * (y + 1) + 4 * (x + 1) + facing
+        .map(|wrap| {
+            let from = describe(wrap.from, wrap.from.2);
+            let to = describe(wrap.to, (wrap.to.2 + 2) % 4);
+            let blocked = if wrap.is_blocked {
+                " (blocked by a wall)"
+            } else {
+                ""
+            };
+            format!("{from} -> {to}{blocked}")
+        })
+        .join("\n")
 }
 
-pub fn run(input: &str) {
+pub fn parse(input: &str) -> (Vec<Vec<u8>>, Vec<Action>) {
     let (board, path) = input.split("\n\n").collect_tuple().unwrap();
     let board: Vec<Vec<u8>> = board
         .lines()
@@ -128,10 +337,42 @@ pub fn run(input: &str) {
             }
         })
         .collect_vec();
+    (board, actions)
+}
+
+pub fn part1((board, actions): &(Vec<Vec<u8>>, Vec<Action>)) -> i32 {
+    get_password(board, actions, false)
+}
+
+pub fn part2((board, actions): &(Vec<Vec<u8>>, Vec<Action>)) -> i32 {
+    get_password(board, actions, true)
+}
 
-    let password = get_password(&board, &actions, false);
-    println!("{password}");
+pub const REPL_HELP: &str = "\
+trace [flat|cube] draw the path over the board (default cube)
+wraps [flat|cube] list the wrap transitions along the path (default cube)";
 
-    let password = get_password(&board, &actions, true);
-    println!("{password}");
+pub fn repl_command(
+    (board, actions): &mut (Vec<Vec<u8>>, Vec<Action>),
+    command: &str,
+    args: &[&str],
+) -> CommandResult {
+    let is_cube = match arg(args, 0, Some("cube".to_string())).as_deref() {
+        Ok("cube") => Ok(true),
+        Ok("flat") => Ok(false),
+        Ok(mode) => Err(format!("unknown mode {mode}, expected flat or cube")),
+        Err(error) => Err(error.clone()),
+    };
+    let result = match command {
+        "trace" => is_cube.map(|is_cube| {
+            let (password, trace) = trace_path(board, actions, is_cube);
+            format!("{}\nPassword: {password}", render_trace(board, &trace))
+        }),
+        "wraps" => is_cube.map(|is_cube| {
+            let (_, trace) = trace_path(board, actions, is_cube);
+            describe_wraps(board, &trace)
+        }),
+        _ => return None,
+    };
+    Some(result)
 }
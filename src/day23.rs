@@ -101,8 +101,8 @@ fn first_stable_round(positions: &HashSet<(i32, i32)>) -> usize {
     unreachable!()
 }
 
-pub fn run(input: &str) {
-    let positions: HashSet<(i32, i32)> = input
+pub fn parse(input: &str) -> HashSet<(i32, i32)> {
+    input
         .lines()
         .enumerate()
         .flat_map(|(y, line)| {
@@ -111,11 +111,13 @@ pub fn run(input: &str) {
                 .filter(|&(_, b)| b == b'#')
                 .map(move |(x, _)| (x as i32, y as i32))
         })
-        .collect();
+        .collect()
+}
 
-    let num_tiles = num_empty_ground_tiles(&positions, 10);
-    println!("{num_tiles}");
+pub fn part1(positions: &HashSet<(i32, i32)>) -> usize {
+    num_empty_ground_tiles(positions, 10)
+}
 
-    let round_index = first_stable_round(&positions);
-    println!("{round_index}");
+pub fn part2(positions: &HashSet<(i32, i32)>) -> usize {
+    first_stable_round(positions)
 }
@@ -75,7 +75,30 @@ fn find_shortest_path(
     total_cost
 }
 
-pub fn run(input: &str) {
+pub struct Valley {
+    vertical_blizzards: Vec<Vec<Blizzard>>,
+    horizontal_blizzards: Vec<Vec<Blizzard>>,
+    width: i32,
+    height: i32,
+    entrance: (i32, i32),
+    exit: (i32, i32),
+}
+
+impl Valley {
+    fn shortest_path(&self, from: (i32, i32), to: (i32, i32), start_time: i32) -> i32 {
+        find_shortest_path(
+            &self.vertical_blizzards,
+            &self.horizontal_blizzards,
+            self.width,
+            self.height,
+            from,
+            to,
+            start_time,
+        )
+    }
+}
+
+pub fn parse(input: &str) -> Valley {
     let valley = input.lines().map(|line| line.as_bytes()).collect_vec();
     let (width, height) = (valley[0].len() as i32, valley.len() as i32);
     let entrance = (valley[0].iter().position(|&b| b == b'.').unwrap() as i32, 0);
@@ -128,35 +151,27 @@ pub fn run(input: &str) {
         })
     });
 
-    let min_minutes_to_exit = find_shortest_path(
-        &vertical_blizzards,
-        &horizontal_blizzards,
+    Valley {
+        vertical_blizzards,
+        horizontal_blizzards,
         width,
         height,
         entrance,
         exit,
-        0,
-    );
-    println!("{min_minutes_to_exit}");
+    }
+}
 
-    let min_minutes_back = find_shortest_path(
-        &vertical_blizzards,
-        &horizontal_blizzards,
-        width,
-        height,
-        exit,
-        entrance,
-        min_minutes_to_exit,
-    );
-    let min_minutes_to_exit_again = find_shortest_path(
-        &vertical_blizzards,
-        &horizontal_blizzards,
-        width,
-        height,
-        entrance,
-        exit,
+pub fn part1(valley: &Valley) -> i32 {
+    valley.shortest_path(valley.entrance, valley.exit, 0)
+}
+
+pub fn part2(valley: &Valley) -> i32 {
+    let min_minutes_to_exit = valley.shortest_path(valley.entrance, valley.exit, 0);
+    let min_minutes_back = valley.shortest_path(valley.exit, valley.entrance, min_minutes_to_exit);
+    let min_minutes_to_exit_again = valley.shortest_path(
+        valley.entrance,
+        valley.exit,
         min_minutes_to_exit + min_minutes_back,
     );
-    let min_total = min_minutes_to_exit + min_minutes_back + min_minutes_to_exit_again;
-    println!("{min_total}");
+    min_minutes_to_exit + min_minutes_back + min_minutes_to_exit_again
 }
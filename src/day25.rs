@@ -1,6 +1,6 @@
 const SNAFU_DIGITS: [char; 5] = ['=', '-', '0', '1', '2'];
 
-fn parse_snafu(s: &str) -> i64 {
+pub fn parse_snafu(s: &str) -> i64 {
     let mut value = 0;
     for c in s.chars() {
         value *= 5;
@@ -16,7 +16,7 @@ fn parse_snafu(s: &str) -> i64 {
     value
 }
 
-fn to_snafu(value: i64) -> String {
+pub fn to_snafu(value: i64) -> String {
     let mut value_up = value.abs();
     let mut value_down = value_up;
     let mut power = 1;
@@ -38,7 +38,10 @@ fn to_snafu(value: i64) -> String {
     reversed_snafu.iter().rev().collect()
 }
 
-pub fn run(input: &str) {
-    let total_snafus = input.lines().map(parse_snafu).sum();
-    println!("{}", to_snafu(total_snafus));
+pub fn parse(input: &str) -> Vec<i64> {
+    input.lines().map(parse_snafu).collect()
+}
+
+pub fn part1(values: &[i64]) -> String {
+    to_snafu(values.iter().sum())
 }
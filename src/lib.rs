@@ -23,4 +23,11 @@ pub mod day22;
 pub mod day23;
 pub mod day24;
 pub mod day25;
+pub mod memory;
+pub mod polynomial;
+#[cfg(feature = "python")]
+mod python;
+pub mod registry;
+pub mod repl;
+pub mod server;
 pub mod utils;
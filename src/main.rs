@@ -1,6 +1,16 @@
+use aoc2022::memory::{self, CountingAllocator, MemoryStats};
+use aoc2022::registry::{self, Step};
+use aoc2022::{day16, repl, server};
+use itertools::Itertools;
+use serde_json::{json, Value};
 use std::env;
 use std::fs;
-use std::time::Instant;
+use std::process;
+use std::time::{Duration, Instant};
+
+/// Only counts allocations when running with --mem-profile
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
 
 fn elapsed_since(start_time: &Instant) -> String {
     let elapsed = start_time.elapsed().as_micros();
@@ -15,56 +25,193 @@ fn elapsed_since(start_time: &Instant) -> String {
     }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    let days: Vec<_> = match args.len() {
-        1 => (1..=25).collect(),
-        _ => args.iter().skip(1).map(|d| d.parse().unwrap()).collect(),
-    };
+fn print_memory_stats(steps: &[Step]) {
+    let stats = steps.iter().filter_map(|step| step.memory).collect_vec();
+    if stats.is_empty() {
+        return;
+    }
+    let total = stats
+        .iter()
+        .fold(MemoryStats::default(), |total, s| total.merge(s));
+    println!("Memory:      peak  allocations  allocated");
+    let names = steps.iter().map(|step| step.name).chain(["total"]);
+    for (name, stats) in names.zip(stats.iter().chain([&total])) {
+        println!(
+            "  {name:<6} {:>9} {:>12} {:>10}",
+            memory::format_bytes(stats.peak_bytes),
+            stats.num_allocations,
+            memory::format_bytes(stats.allocated_bytes)
+        );
+    }
+}
+
+fn steps_to_json(day: u32, steps: &[Step]) -> Value {
+    let json_steps = steps
+        .iter()
+        .map(|step| {
+            json!({
+                "name": step.name,
+                "answer": step.answer,
+                "time_us": step.elapsed.as_micros() as u64,
+                "memory": step.memory,
+            })
+        })
+        .collect_vec();
+    let memory = steps
+        .iter()
+        .filter_map(|step| step.memory)
+        .reduce(|total, stats| total.merge(&stats));
+    json!({
+        "day": day,
+        "steps": json_steps,
+        "time_us": steps.iter().map(|step| step.elapsed.as_micros() as u64).sum::<u64>(),
+        "memory": memory,
+    })
+}
+
+fn run_days(days: &[u32], json_output: bool) {
     let global_start_time = Instant::now();
-    for day in &days {
-        println!("Day {}:", day);
+    let mut json_days = vec![];
+    for &day in days {
+        if !json_output {
+            println!("Day {}:", day);
+        }
         let path = format!("./data/day{:02}.txt", day);
         let input = fs::read_to_string(&path);
         let start_time = Instant::now();
         if let Ok(input) = input {
             let input = input.trim_end();
-            let day_func = match day {
-                1 => aoc2022::day01::run,
-                2 => aoc2022::day02::run,
-                3 => aoc2022::day03::run,
-                4 => aoc2022::day04::run,
-                5 => aoc2022::day05::run,
-                6 => aoc2022::day06::run,
-                7 => aoc2022::day07::run,
-                8 => aoc2022::day08::run,
-                9 => aoc2022::day09::run,
-                10 => aoc2022::day10::run,
-                11 => aoc2022::day11::run,
-                12 => aoc2022::day12::run,
-                13 => aoc2022::day13::run,
-                14 => aoc2022::day14::run,
-                15 => aoc2022::day15::run,
-                16 => aoc2022::day16::run,
-                17 => aoc2022::day17::run,
-                18 => aoc2022::day18::run,
-                19 => aoc2022::day19::run,
-                20 => aoc2022::day20::run,
-                21 => aoc2022::day21::run,
-                22 => aoc2022::day22::run,
-                23 => aoc2022::day23::run,
-                24 => aoc2022::day24::run,
-                25 => aoc2022::day25::run,
-                _ => unreachable!(),
+            let steps = match (registry::get_day(day).unwrap().solve)(input) {
+                Ok(steps) => steps,
+                Err(error) if json_output => {
+                    json_days.push(json!({"day": day, "error": error}));
+                    continue;
+                }
+                Err(error) => {
+                    println!("ERROR: {error}\n");
+                    continue;
+                }
             };
-            day_func(input);
+            if json_output {
+                json_days.push(steps_to_json(day, &steps));
+                continue;
+            }
+            for step in &steps {
+                if let Some(answer) = &step.answer {
+                    println!("{answer}");
+                }
+            }
             println!("Time: {}", elapsed_since(&start_time));
+            print_memory_stats(&steps);
+        } else if json_output {
+            json_days.push(json!({"day": day, "error": "no data"}));
+            continue;
         } else {
             println!("ERROR: no data");
         }
         println!();
     }
-    if days.len() > 1 {
+    if json_output {
+        let total_time = global_start_time.elapsed().as_micros() as u64;
+        println!(
+            "{}",
+            json!({"days": json_days, "total_time_us": total_time})
+        );
+    } else if days.len() > 1 {
         println!("TOTAL TIME: {}", elapsed_since(&global_start_time));
     }
 }
+
+const SERVE_USAGE: &str = "usage: aoc2022 serve [--address <host:port>] \
+[--max-body-size <bytes>] [--timeout <seconds>] [--max-running-solvers <count>]";
+
+fn parse_serve_options(args: &[String]) -> Result<server::Config, String> {
+    let mut config = server::Config::default();
+    let mut args = args.iter();
+    while let Some(option) = args.next() {
+        if ![
+            "--address",
+            "--max-body-size",
+            "--timeout",
+            "--max-running-solvers",
+        ]
+        .contains(&option.as_str())
+        {
+            return Err(format!("unknown option {option}"));
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {option}"))?;
+        let invalid = || format!("invalid value for {option}: {value}");
+        match option.as_str() {
+            "--address" => config.address = value.clone(),
+            "--max-body-size" => config.max_body_size = value.parse().map_err(|_| invalid())?,
+            "--timeout" => {
+                config.timeout = value
+                    .parse()
+                    .ok()
+                    .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                    .ok_or_else(invalid)?
+            }
+            _ => {
+                config.max_running_solvers = value
+                    .parse()
+                    .ok()
+                    .filter(|&count| count > 0)
+                    .ok_or_else(invalid)?
+            }
+        }
+    }
+    Ok(config)
+}
+
+fn serve(args: &[String]) {
+    let config = parse_serve_options(args).unwrap_or_else(|error| {
+        eprintln!("ERROR: {error}\n{SERVE_USAGE}");
+        process::exit(2);
+    });
+    if let Err(error) = server::serve(config) {
+        eprintln!("ERROR: {error}");
+        process::exit(1);
+    }
+}
+
+fn repl(args: &[String]) {
+    let day = args[0].parse().unwrap();
+    let path = match args.get(1) {
+        Some(path) => path.clone(),
+        None => format!("./data/day{:02}.txt", day),
+    };
+    let input = fs::read_to_string(path).unwrap();
+    repl::repl(registry::get_day(day).unwrap(), input.trim_end());
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(String::as_str) == Some("serve") {
+        serve(&args[2..]);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("repl") {
+        repl(&args[2..]);
+        return;
+    }
+    let (options, days): (Vec<_>, Vec<_>) =
+        args.iter().skip(1).partition(|arg| arg.starts_with("--"));
+    let mut json_output = false;
+    for option in options {
+        match option.as_str() {
+            "--mem-profile" => memory::enable(),
+            "--json" => json_output = true,
+            _ => match option.strip_prefix("--day16-solver=") {
+                Some(name) => day16::set_solver(name.parse().unwrap_or_else(|e| panic!("{e}"))),
+                None => panic!("unknown option {option}"),
+            },
+        }
+    }
+    let days: Vec<_> = match days.len() {
+        0 => (1..=25).collect(),
+        _ => days.iter().map(|d| d.parse().unwrap()).collect(),
+    };
+    run_days(&days, json_output);
+}
@@ -0,0 +1,132 @@
+use serde::Serialize;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering::Relaxed};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static LIVE_BYTES: AtomicI64 = AtomicI64::new(0);
+static PEAK_BYTES: AtomicI64 = AtomicI64::new(0);
+static NUM_ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// A global allocator that delegates to the system allocator, and counts the
+/// allocations once profiling is enabled. To use it, the binary must declare:
+/// `#[global_allocator] static ALLOCATOR: CountingAllocator = CountingAllocator;`
+pub struct CountingAllocator;
+
+fn record_allocation(size: usize) {
+    NUM_ALLOCATIONS.fetch_add(1, Relaxed);
+    ALLOCATED_BYTES.fetch_add(size as u64, Relaxed);
+    let live = LIVE_BYTES.fetch_add(size as i64, Relaxed) + size as i64;
+    PEAK_BYTES.fetch_max(live, Relaxed);
+}
+
+fn record_deallocation(size: usize) {
+    LIVE_BYTES.fetch_sub(size as i64, Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() && ENABLED.load(Relaxed) {
+            record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() && ENABLED.load(Relaxed) {
+            record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        if ENABLED.load(Relaxed) {
+            record_deallocation(layout.size());
+        }
+    }
+
+    /// A reallocation counts as a new allocation of the new size
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() && ENABLED.load(Relaxed) {
+            record_deallocation(layout.size());
+            record_allocation(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Starts counting allocations. Memory allocated before this call is not
+/// tracked, so it should be called as early as possible.
+pub fn enable() {
+    ENABLED.store(true, Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Relaxed)
+}
+
+/// Memory usage over a period of time, e.g., while solving a part.
+/// The peak is the maximum heap size reached during the period, including
+/// memory that was already allocated when the period started.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct MemoryStats {
+    pub peak_bytes: u64,
+    pub num_allocations: u64,
+    pub allocated_bytes: u64,
+}
+
+impl MemoryStats {
+    /// Combines the stats of consecutive periods
+    pub fn merge(&self, other: &MemoryStats) -> MemoryStats {
+        MemoryStats {
+            peak_bytes: self.peak_bytes.max(other.peak_bytes),
+            num_allocations: self.num_allocations + other.num_allocations,
+            allocated_bytes: self.allocated_bytes + other.allocated_bytes,
+        }
+    }
+}
+
+/// Measures the memory usage from its creation until `stop()` is called.
+/// Probes must not overlap, since each probe resets the peak heap size.
+pub struct Probe {
+    num_allocations: u64,
+    allocated_bytes: u64,
+}
+
+impl Probe {
+    /// Returns `None` if profiling is not enabled.
+    pub fn start() -> Option<Probe> {
+        if !is_enabled() {
+            return None;
+        }
+        PEAK_BYTES.store(LIVE_BYTES.load(Relaxed), Relaxed);
+        Some(Probe {
+            num_allocations: NUM_ALLOCATIONS.load(Relaxed),
+            allocated_bytes: ALLOCATED_BYTES.load(Relaxed),
+        })
+    }
+
+    pub fn stop(self) -> MemoryStats {
+        MemoryStats {
+            peak_bytes: PEAK_BYTES.load(Relaxed).max(0) as u64,
+            num_allocations: NUM_ALLOCATIONS.load(Relaxed) - self.num_allocations,
+            allocated_bytes: ALLOCATED_BYTES.load(Relaxed) - self.allocated_bytes,
+        }
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes >= 1 << 30 {
+        format!("{:.1}GiB", bytes as f64 / (1u64 << 30) as f64)
+    } else if bytes >= 1 << 20 {
+        format!("{:.1}MiB", bytes as f64 / (1u64 << 20) as f64)
+    } else if bytes >= 1 << 10 {
+        format!("{:.1}KiB", bytes as f64 / (1u64 << 10) as f64)
+    } else {
+        format!("{bytes}B")
+    }
+}
@@ -0,0 +1,344 @@
+//! Exact polynomials and rational functions of one variable x, with big
+//! rational coefficients.
+use itertools::Itertools;
+use num::{BigInt, BigRational, Integer, One, Signed, Zero};
+use std::fmt;
+
+/// The largest constant term that `integer_roots` factorizes, by trial division
+const MAX_FACTORIZED_CONSTANT: u64 = 100_000_000_000_000;
+
+/// Coefficients, from the constant term up to the leading one, which is never
+/// zero (the zero polynomial has no coefficients).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Polynomial(Vec<BigRational>);
+
+impl Polynomial {
+    pub fn new(mut coefficients: Vec<BigRational>) -> Self {
+        while coefficients.last().is_some_and(|c| c.is_zero()) {
+            coefficients.pop();
+        }
+        Self(coefficients)
+    }
+
+    pub fn constant(value: BigRational) -> Self {
+        Self::new(vec![value])
+    }
+
+    pub fn x() -> Self {
+        Self::new(vec![BigRational::zero(), BigRational::one()])
+    }
+
+    pub fn coefficients(&self) -> &[BigRational] {
+        &self.0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns `None` for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.0.len().checked_sub(1)
+    }
+
+    /// Returns the value if the polynomial is a constant
+    pub fn as_constant(&self) -> Option<BigRational> {
+        match self.0.len() {
+            0 => Some(BigRational::zero()),
+            1 => Some(self.0[0].clone()),
+            _ => None,
+        }
+    }
+
+    fn leading(&self) -> BigRational {
+        self.0.last().cloned().unwrap_or_else(BigRational::zero)
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        let len = self.0.len().max(other.0.len());
+        let zero = BigRational::zero();
+        Self::new(
+            (0..len)
+                .map(|i| self.0.get(i).unwrap_or(&zero) + other.0.get(i).unwrap_or(&zero))
+                .collect(),
+        )
+    }
+
+    pub fn neg(&self) -> Self {
+        Self(self.0.iter().map(|c| -c).collect())
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        if self.is_zero() || other.is_zero() {
+            return Self(vec![]);
+        }
+        let mut product = vec![BigRational::zero(); self.0.len() + other.0.len() - 1];
+        for (i, a) in self.0.iter().enumerate() {
+            for (j, b) in other.0.iter().enumerate() {
+                product[i + j] += a * b;
+            }
+        }
+        Self::new(product)
+    }
+
+    pub fn scale(&self, factor: &BigRational) -> Self {
+        Self::new(self.0.iter().map(|c| c * factor).collect())
+    }
+
+    /// Euclidean division: returns (quotient, remainder). Panics if the
+    /// divisor is zero.
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        let divisor_degree = divisor.degree().expect("division by the zero polynomial");
+        let mut remainder = self.clone();
+        let mut quotient = vec![BigRational::zero(); self.0.len().saturating_sub(divisor_degree)];
+        while let Some(degree) = remainder.degree().filter(|&d| d >= divisor_degree) {
+            let factor = remainder.leading() / divisor.leading();
+            let shift = degree - divisor_degree;
+            for (i, c) in divisor.0.iter().enumerate() {
+                remainder.0[i + shift] -= &factor * c;
+            }
+            quotient[shift] = factor;
+            remainder = Self::new(remainder.0);
+        }
+        (Self::new(quotient), remainder)
+    }
+
+    /// Returns the same polynomial, divided by its leading coefficient
+    pub fn monic(&self) -> Self {
+        if self.is_zero() {
+            return self.clone();
+        }
+        self.scale(&self.leading().recip())
+    }
+
+    /// Greatest common divisor, as a monic polynomial
+    pub fn gcd(&self, other: &Self) -> Self {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while !b.is_zero() {
+            let (_, remainder) = a.div_rem(&b);
+            (a, b) = (b, remainder);
+        }
+        a.monic()
+    }
+
+    pub fn eval(&self, x: &BigRational) -> BigRational {
+        self.0
+            .iter()
+            .rev()
+            .fold(BigRational::zero(), |value, c| value * x + c)
+    }
+
+    /// Returns all the distinct integer roots, in increasing order. Degrees 1
+    /// and 2 are solved directly. For higher degrees, we use the rational root
+    /// theorem: any integer root divides the constant term (once the factors of
+    /// x are removed), so this only works if it's small enough to factorize:
+    /// returns `None` if it isn't. Panics if the polynomial is zero, since
+    /// every integer is a root.
+    pub fn integer_roots(&self) -> Option<Vec<BigInt>> {
+        assert!(!self.is_zero(), "every integer is a root of zero");
+        // scale to integer coefficients, and factor out x^k
+        let lcm = self
+            .0
+            .iter()
+            .fold(BigInt::one(), |lcm, c| lcm.lcm(c.denom()));
+        let mut coefficients = self.0.iter().map(|c| (c * &lcm).to_integer()).collect_vec();
+        let mut roots = vec![];
+        let num_zero_roots = coefficients.iter().take_while(|c| c.is_zero()).count();
+        if num_zero_roots > 0 {
+            roots.push(BigInt::zero());
+            coefficients.drain(..num_zero_roots);
+        }
+        let exact_div = |a: BigInt, b: &BigInt| {
+            let (quotient, remainder) = a.div_rem(b);
+            remainder.is_zero().then_some(quotient)
+        };
+        match coefficients.len() - 1 {
+            0 => {}
+            1 => roots.extend(exact_div(-&coefficients[0], &coefficients[1])),
+            2 => {
+                let (c, b, a) = (&coefficients[0], &coefficients[1], &coefficients[2]);
+                let discriminant = b * b - BigInt::from(4) * a * c;
+                if !discriminant.is_negative() {
+                    let sqrt = discriminant.sqrt();
+                    if &sqrt * &sqrt == discriminant {
+                        for sqrt in [sqrt.clone(), -sqrt] {
+                            roots.extend(exact_div(-b + sqrt, &(BigInt::from(2) * a)));
+                        }
+                    }
+                }
+            }
+            _ => {
+                let constant = coefficients[0].abs();
+                if constant > BigInt::from(MAX_FACTORIZED_CONSTANT) {
+                    return None;
+                }
+                let constant = u64::try_from(constant).unwrap();
+                let candidates = (1..)
+                    .take_while(|d| d * d <= constant)
+                    .filter(|d| constant % d == 0)
+                    .flat_map(|d| [d, constant / d])
+                    .flat_map(|d| [BigInt::from(d), -BigInt::from(d)]);
+                let integer_polynomial = Self::new(
+                    coefficients
+                        .iter()
+                        .map(|c| BigRational::from_integer(c.clone()))
+                        .collect(),
+                );
+                roots.extend(candidates.filter(|root| {
+                    integer_polynomial
+                        .eval(&BigRational::from_integer(root.clone()))
+                        .is_zero()
+                }));
+            }
+        }
+        roots.sort();
+        roots.dedup();
+        Some(roots)
+    }
+}
+
+fn write_coefficient(f: &mut fmt::Formatter, c: &BigRational, power: usize) -> fmt::Result {
+    let c_is_one = c.abs().is_one();
+    match power {
+        0 => write!(f, "{}", c.abs()),
+        _ if c_is_one => Ok(()),
+        _ if c.is_integer() => write!(f, "{}", c.abs()),
+        _ => write!(f, "({})", c.abs()),
+    }?;
+    match power {
+        0 => Ok(()),
+        1 => write!(f, "x"),
+        _ => write!(f, "x^{power}"),
+    }
+}
+
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let terms = self
+            .0
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, c)| !c.is_zero());
+        for (index, (power, c)) in terms.enumerate() {
+            match (index, c.is_negative()) {
+                (0, true) => write!(f, "-")?,
+                (0, false) => {}
+                (_, true) => write!(f, " - ")?,
+                (_, false) => write!(f, " + ")?,
+            }
+            write_coefficient(f, c, power)?;
+        }
+        Ok(())
+    }
+}
+
+/// A quotient of two polynomials, always kept in lowest terms, with a monic
+/// denominator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RationalFunction {
+    pub numerator: Polynomial,
+    pub denominator: Polynomial,
+}
+
+impl RationalFunction {
+    pub fn new(numerator: Polynomial, denominator: Polynomial) -> Self {
+        assert!(!denominator.is_zero(), "division by zero");
+        if let Some(denominator) = denominator.as_constant() {
+            // fast path, e.g. when evaluating constants
+            return Self::from_polynomial(numerator.scale(&denominator.recip()));
+        }
+        let gcd = numerator.gcd(&denominator);
+        let (numerator, _) = numerator.div_rem(&gcd);
+        let (denominator, _) = denominator.div_rem(&gcd);
+        let leading = denominator.leading().recip();
+        Self {
+            numerator: numerator.scale(&leading),
+            denominator: denominator.scale(&leading),
+        }
+    }
+
+    pub fn from_polynomial(polynomial: Polynomial) -> Self {
+        Self {
+            numerator: polynomial,
+            denominator: Polynomial::constant(BigRational::one()),
+        }
+    }
+
+    /// Returns the value if this is a constant
+    pub fn as_constant(&self) -> Option<BigRational> {
+        let denominator = self.denominator.as_constant()?;
+        Some(self.numerator.as_constant()? / denominator)
+    }
+
+    /// Since the denominator is monic, it's constant iff it's 1
+    fn is_polynomial(&self) -> bool {
+        self.denominator.degree() == Some(0)
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        if self.is_polynomial() && other.is_polynomial() {
+            return Self::from_polynomial(self.numerator.add(&other.numerator));
+        }
+        Self::new(
+            self.numerator
+                .mul(&other.denominator)
+                .add(&other.numerator.mul(&self.denominator)),
+            self.denominator.mul(&other.denominator),
+        )
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        if self.is_polynomial() && other.is_polynomial() {
+            return Self::from_polynomial(self.numerator.sub(&other.numerator));
+        }
+        Self::new(
+            self.numerator
+                .mul(&other.denominator)
+                .sub(&other.numerator.mul(&self.denominator)),
+            self.denominator.mul(&other.denominator),
+        )
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        if self.is_polynomial() && other.is_polynomial() {
+            return Self::from_polynomial(self.numerator.mul(&other.numerator));
+        }
+        Self::new(
+            self.numerator.mul(&other.numerator),
+            self.denominator.mul(&other.denominator),
+        )
+    }
+
+    /// Panics if the divisor is zero
+    pub fn div(&self, other: &Self) -> Self {
+        if let Some(divisor) = other.as_constant() {
+            assert!(!divisor.is_zero(), "division by zero");
+            return Self {
+                numerator: self.numerator.scale(&divisor.recip()),
+                denominator: self.denominator.clone(),
+            };
+        }
+        Self::new(
+            self.numerator.mul(&other.denominator),
+            self.denominator.mul(&other.numerator),
+        )
+    }
+}
+
+impl fmt::Display for RationalFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.denominator.as_constant().is_some() {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "({}) / ({})", self.numerator, self.denominator)
+        }
+    }
+}
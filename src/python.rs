@@ -0,0 +1,289 @@
+//! Python bindings, built with the `python` feature. Each day is exposed as a
+//! submodule with `parse(input)`, which returns an opaque `Puzzle`, and
+//! `part1(puzzle)` and `part2(puzzle)`, which return native Python values.
+
+// false positive triggered by the #[pyfunction] macro expansion
+#![allow(clippy::useless_conversion)]
+use super::day12::BestStart;
+use super::day15::{Area, SensorField};
+use super::day21::Solution;
+use super::polynomial::Polynomial;
+use super::registry::{self, DAYS};
+use num::BigInt;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyModule};
+use std::any::Any;
+
+/// The parsed input of a day, to be passed to the parts of the same day.
+#[pyclass(unsendable)]
+struct Puzzle {
+    #[pyo3(get)]
+    day: u32,
+    data: Box<dyn Any>,
+}
+
+#[pymethods]
+impl Puzzle {
+    fn __repr__(&self) -> String {
+        format!("<Puzzle day={}>", self.day)
+    }
+}
+
+impl Puzzle {
+    /// Gives access to the parsed data, typed after the day's parse function.
+    fn data<T: 'static>(&self, _parse: fn(&str) -> T) -> PyResult<&T> {
+        self.data
+            .downcast_ref()
+            .ok_or_else(|| PyTypeError::new_err(format!("this puzzle is for day {}", self.day)))
+    }
+}
+
+macro_rules! day_module {
+    ($day:literal, $module:ident, $($part:ident),+) => {
+        mod $module {
+            use super::*;
+
+            /// Parses the puzzle input
+            #[pyfunction]
+            fn parse(input: &str) -> Puzzle {
+                Puzzle {
+                    day: $day,
+                    data: Box::new(crate::$module::parse(input.trim_end())),
+                }
+            }
+
+            $(
+                #[pyfunction]
+                fn $part(py: Python, puzzle: &Puzzle) -> PyResult<PyObject> {
+                    let data = puzzle.data(crate::$module::parse)?;
+                    Ok(crate::$module::$part(data).into_py(py))
+                }
+            )+
+
+            pub fn register<'py>(parent: &Bound<'py, PyModule>) -> PyResult<Bound<'py, PyModule>> {
+                let module = PyModule::new_bound(parent.py(), stringify!($module))?;
+                module.add_function(wrap_pyfunction!(parse, &module)?)?;
+                $(module.add_function(wrap_pyfunction!($part, &module)?)?;)+
+                parent.add_submodule(&module)?;
+                Ok(module)
+            }
+        }
+    };
+}
+
+/// Day 12's part 2 is returned as (steps, (x, y)), with the starting square
+impl IntoPy<PyObject> for BestStart {
+    fn into_py(self, py: Python) -> PyObject {
+        (self.steps, self.start).into_py(py)
+    }
+}
+
+day_module!(1, day01, part1, part2);
+day_module!(2, day02, part1, part2);
+day_module!(3, day03, part1, part2);
+day_module!(4, day04, part1, part2);
+day_module!(5, day05, part1, part2);
+day_module!(6, day06, part1, part2);
+day_module!(7, day07, part1, part2);
+day_module!(8, day08, part1, part2);
+day_module!(9, day09, part1, part2);
+day_module!(10, day10, part1, part2);
+day_module!(11, day11, part1, part2);
+day_module!(12, day12, part1, part2);
+day_module!(13, day13, part1, part2);
+day_module!(14, day14, part1, part2);
+day_module!(15, day15, part1, part2);
+day_module!(16, day16, part1, part2);
+day_module!(17, day17, part1, part2);
+day_module!(18, day18, part1, part2);
+day_module!(19, day19, part1, part2);
+day_module!(20, day20, part1, part2);
+day_module!(21, day21, part1);
+day_module!(22, day22, part1, part2);
+day_module!(23, day23, part1, part2);
+day_module!(24, day24, part1, part2);
+day_module!(25, day25, part1);
+
+/// Converts a SNAFU number to an integer
+#[pyfunction]
+fn parse_snafu(snafu: &str) -> PyResult<i64> {
+    if snafu.is_empty() || !snafu.chars().all(|c| "=-012".contains(c)) {
+        return Err(PyValueError::new_err(format!(
+            "invalid SNAFU number: {snafu:?}"
+        )));
+    }
+    Ok(crate::day25::parse_snafu(snafu))
+}
+
+/// Converts an integer to a SNAFU number
+#[pyfunction]
+fn to_snafu(value: i64) -> String {
+    crate::day25::to_snafu(value)
+}
+
+/// Evaluates the given monkey, treating `humn` as the unknown x if
+/// `with_human` is true. Returns the coefficients of the numerator and of the
+/// denominator as lists of fractions, from the constant term up.
+#[pyfunction]
+#[pyo3(signature = (puzzle, name, with_human=false))]
+fn evaluate_monkey(
+    py: Python,
+    puzzle: &Puzzle,
+    name: &str,
+    with_human: bool,
+) -> PyResult<(Vec<PyObject>, Vec<PyObject>)> {
+    let monkeys = puzzle.data(crate::day21::parse)?;
+    if !monkeys.contains_key(name) {
+        return Err(PyValueError::new_err(format!("unknown monkey {name}")));
+    }
+    let mut monkeys = monkeys.clone();
+    if with_human {
+        monkeys.insert(
+            "humn".to_string(),
+            crate::day21::MonkeyBusiness::HumanVariable,
+        );
+    }
+    let function = crate::day21::evaluate(&monkeys, name);
+    let fraction = py.import_bound("fractions")?.getattr("Fraction")?;
+    let to_fractions = |polynomial: &Polynomial| {
+        polynomial
+            .coefficients()
+            .iter()
+            .map(|c| {
+                Ok(fraction
+                    .call1((c.numer().clone(), c.denom().clone()))?
+                    .unbind())
+            })
+            .collect::<PyResult<Vec<_>>>()
+    };
+    Ok((
+        to_fractions(&function.numerator)?,
+        to_fractions(&function.denominator)?,
+    ))
+}
+
+fn sensor_field(puzzle: &Puzzle) -> PyResult<SensorField> {
+    Ok(SensorField::new(puzzle.data(crate::day15::parse)?))
+}
+
+/// The indices of the sensors covering the given position
+#[pyfunction]
+fn sensors_covering(puzzle: &Puzzle, x: i64, y: i64) -> PyResult<Vec<usize>> {
+    Ok(sensor_field(puzzle)?.sensors_covering(x, y))
+}
+
+/// The ranges of x covered on the given row, as (min, max) tuples
+#[pyfunction]
+fn row_coverage(puzzle: &Puzzle, y: i64) -> PyResult<Vec<(i64, i64)>> {
+    Ok(sensor_field(puzzle)?.row_coverage(y))
+}
+
+/// The ranges of y covered on the given column, as (min, max) tuples
+#[pyfunction]
+fn column_coverage(puzzle: &Puzzle, x: i64) -> PyResult<Vec<(i64, i64)>> {
+    Ok(sensor_field(puzzle)?.column_coverage(x))
+}
+
+/// The uncovered position closest to the given one, in Manhattan distance
+#[pyfunction]
+fn nearest_uncovered(puzzle: &Puzzle, x: i64, y: i64) -> PyResult<(i64, i64)> {
+    Ok(sensor_field(puzzle)?.nearest_uncovered(x, y))
+}
+
+/// Removes the given sensor, and returns the number of positions of the
+/// square search area that are no longer covered, and the number of positions
+/// that no sensor covers anymore
+#[pyfunction]
+#[pyo3(signature = (puzzle, index, min=0, max=4_000_000))]
+fn removal_effect(puzzle: &Puzzle, index: usize, min: i64, max: i64) -> PyResult<(i64, i64)> {
+    let field = sensor_field(puzzle)?;
+    if index >= field.sensors().len() {
+        return Err(PyValueError::new_err(format!("unknown sensor {index}")));
+    }
+    let effect = field.removal_effect(index, &Area::square(min, max));
+    let num_uncovered = effect.uncovered.iter().map(|region| region.size).sum();
+    Ok((effect.lost_coverage, num_uncovered))
+}
+
+/// Returns a list of integer roots, which is empty if there's no solution, or
+/// `None` if there are infinitely many solutions. Raises `ValueError` if the
+/// equation can't be solved.
+#[pyfunction]
+#[pyo3(name = "part2")]
+fn monkey_riddle_part2(py: Python, puzzle: &Puzzle) -> PyResult<PyObject> {
+    match crate::day21::part2(puzzle.data(crate::day21::parse)?) {
+        Solution::NoSolution => Ok(Vec::<BigInt>::new().into_py(py)),
+        Solution::Roots(roots) => Ok(roots.into_py(py)),
+        Solution::InfinitelyMany => Ok(py.None()),
+        solution @ Solution::CannotSolve { .. } => Err(PyValueError::new_err(solution.to_string())),
+    }
+}
+
+/// Solves a whole day, returning a dict with the answers and the time taken
+/// by each step, in seconds.
+#[pyfunction]
+fn solve(py: Python, day: u32, input: &str) -> PyResult<PyObject> {
+    let day = registry::get_day(day)
+        .ok_or_else(|| PyValueError::new_err(format!("unknown day {day}")))?;
+    let steps = py
+        .allow_threads(|| (day.solve)(input.trim_end()))
+        .map_err(PyValueError::new_err)?;
+    let dict = PyDict::new_bound(py);
+    let answers = steps.iter().filter_map(|step| step.answer.clone());
+    dict.set_item("answers", answers.collect::<Vec<_>>())?;
+    let timings = PyDict::new_bound(py);
+    for step in &steps {
+        timings.set_item(step.name, step.elapsed.as_secs_f64())?;
+    }
+    dict.set_item("timings", timings)?;
+    Ok(dict.into_py(py))
+}
+
+#[pymodule]
+#[pyo3(name = "aoc2022")]
+fn aoc2022_module(module: &Bound<PyModule>) -> PyResult<()> {
+    module.add_class::<Puzzle>()?;
+    module.add_function(wrap_pyfunction!(solve, module)?)?;
+    module.add(
+        "DAYS",
+        DAYS.iter()
+            .map(|day| (day.day, day.title))
+            .collect::<Vec<_>>(),
+    )?;
+    day01::register(module)?;
+    day02::register(module)?;
+    day03::register(module)?;
+    day04::register(module)?;
+    day05::register(module)?;
+    day06::register(module)?;
+    day07::register(module)?;
+    day08::register(module)?;
+    day09::register(module)?;
+    day10::register(module)?;
+    day11::register(module)?;
+    day12::register(module)?;
+    day13::register(module)?;
+    day14::register(module)?;
+    let day15 = day15::register(module)?;
+    day15.add_function(wrap_pyfunction!(sensors_covering, &day15)?)?;
+    day15.add_function(wrap_pyfunction!(row_coverage, &day15)?)?;
+    day15.add_function(wrap_pyfunction!(column_coverage, &day15)?)?;
+    day15.add_function(wrap_pyfunction!(nearest_uncovered, &day15)?)?;
+    day15.add_function(wrap_pyfunction!(removal_effect, &day15)?)?;
+    day16::register(module)?;
+    day17::register(module)?;
+    day18::register(module)?;
+    day19::register(module)?;
+    day20::register(module)?;
+    let day21 = day21::register(module)?;
+    day21.add_function(wrap_pyfunction!(monkey_riddle_part2, &day21)?)?;
+    day21.add_function(wrap_pyfunction!(evaluate_monkey, &day21)?)?;
+    day22::register(module)?;
+    day23::register(module)?;
+    day24::register(module)?;
+    let day25 = day25::register(module)?;
+    day25.add_function(wrap_pyfunction!(parse_snafu, &day25)?)?;
+    day25.add_function(wrap_pyfunction!(to_snafu, &day25)?)?;
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use super::memory::{MemoryStats, Probe};
+use super::*;
+use std::convert::Infallible;
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+/// One step of a day's solution: parsing the input, or solving one of the parts.
+/// The answer is `None` for the parsing step, and the memory stats are only
+/// available when memory profiling is enabled.
+pub struct Step {
+    pub name: &'static str,
+    pub answer: Option<String>,
+    pub elapsed: Duration,
+    pub memory: Option<MemoryStats>,
+}
+
+/// Solves a whole day, or returns why the input is invalid, for the days
+/// that check their input
+pub type Solver = fn(&str) -> Result<Vec<Step>, String>;
+
+pub struct Day {
+    pub day: u32,
+    pub title: &'static str,
+    pub solve: Solver,
+}
+
+type Part<'a, T> = &'a dyn Fn(&T) -> String;
+
+/// Parses the input then solves each part, timing every step.
+pub fn solve<T, E: Display>(
+    input: &str,
+    parse: impl Fn(&str) -> Result<T, E>,
+    parts: &[Part<T>],
+) -> Result<Vec<Step>, String> {
+    let probe = Probe::start();
+    let start_time = Instant::now();
+    let data = parse(input).map_err(|error| error.to_string())?;
+    let mut steps = vec![Step {
+        name: "parse",
+        answer: None,
+        elapsed: start_time.elapsed(),
+        memory: probe.map(Probe::stop),
+    }];
+    for (index, part) in parts.iter().enumerate() {
+        let probe = Probe::start();
+        let start_time = Instant::now();
+        let answer = part(&data);
+        let elapsed = start_time.elapsed();
+        steps.push(Step {
+            name: ["part1", "part2"][index],
+            answer: Some(answer),
+            elapsed,
+            memory: probe.map(Probe::stop),
+        });
+    }
+    Ok(steps)
+}
+
+/// Wraps a parse function that panics on invalid inputs
+pub fn infallible<T>(parse: fn(&str) -> T) -> impl Fn(&str) -> Result<T, Infallible> {
+    move |input| Ok(parse(input))
+}
+
+macro_rules! day {
+    // for the days that report invalid inputs rather than panic
+    ($day:literal, $module:ident, $title:literal, try_parse, $($part:ident),+) => {
+        Day {
+            day: $day,
+            title: $title,
+            solve: |input| solve(input, $module::try_parse, &[$(&|data| $module::$part(data).to_string()),+]),
+        }
+    };
+    ($day:literal, $module:ident, $title:literal, $($part:ident),+) => {
+        Day {
+            day: $day,
+            title: $title,
+            solve: |input| solve(input, infallible($module::parse), &[$(&|data| $module::$part(data).to_string()),+]),
+        }
+    };
+}
+
+pub const DAYS: [Day; 25] = [
+    day!(1, day01, "Calorie Counting", part1, part2),
+    day!(2, day02, "Rock Paper Scissors", part1, part2),
+    day!(3, day03, "Rucksack Reorganization", part1, part2),
+    day!(4, day04, "Camp Cleanup", part1, part2),
+    day!(5, day05, "Supply Stacks", part1, part2),
+    day!(6, day06, "Tuning Trouble", part1, part2),
+    day!(7, day07, "No Space Left On Device", part1, part2),
+    day!(8, day08, "Treetop Tree House", part1, part2),
+    day!(9, day09, "Rope Bridge", part1, part2),
+    day!(10, day10, "Cathode-Ray Tube", part1, part2),
+    day!(11, day11, "Monkey in the Middle", part1, part2),
+    day!(12, day12, "Hill Climbing Algorithm", part1, part2),
+    day!(13, day13, "Distress Signal", part1, part2),
+    day!(14, day14, "Regolith Reservoir", part1, part2),
+    day!(15, day15, "Beacon Exclusion Zone", part1, part2),
+    day!(16, day16, "Proboscidea Volcanium", try_parse, part1, part2),
+    day!(17, day17, "Pyroclastic Flow", part1, part2),
+    day!(18, day18, "Boiling Boulders", part1, part2),
+    day!(19, day19, "Not Enough Minerals", part1, part2),
+    day!(20, day20, "Grove Positioning System", part1, part2),
+    day!(21, day21, "Monkey Math", try_parse, part1, part2),
+    day!(22, day22, "Monkey Map", part1, part2),
+    day!(23, day23, "Unstable Diffusion", part1, part2),
+    day!(24, day24, "Blizzard Basin", part1, part2),
+    day!(25, day25, "Full of Hot Air", part1),
+];
+
+pub fn get_day(day: u32) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.day == day)
+}
@@ -0,0 +1,164 @@
+use super::registry::{infallible, Day};
+use super::{day07, day12, day14, day15, day16, day17, day19, day21, day22};
+use std::fmt::Display;
+use std::io::{self, BufRead, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::str::FromStr;
+
+/// The result of a day-specific command: `None` if the command is unknown,
+/// otherwise the text to print, or an error message.
+pub type CommandResult = Option<Result<String, String>>;
+
+/// Parses the argument at the given index, or returns the default value if
+/// the argument is missing and there is a default.
+pub fn arg<T: FromStr>(args: &[&str], index: usize, default: Option<T>) -> Result<T, String> {
+    match (args.get(index), default) {
+        (Some(arg), _) => arg.parse().map_err(|_| format!("invalid argument: {arg}")),
+        (None, Some(default)) => Ok(default),
+        (None, None) => Err(format!("missing argument #{}", index + 1)),
+    }
+}
+
+const GENERIC_HELP: &str = "\
+solve             run the whole solution (parse, part 1 and part 2)
+reload            parse the input again, discarding any changes
+help              show this help
+quit              exit the REPL";
+
+fn session<T, E: Display>(
+    day: &Day,
+    input: &str,
+    parse: impl Fn(&str) -> Result<T, E>,
+    help: &str,
+    command: fn(&mut T, &str, &[&str]) -> CommandResult,
+) {
+    let mut data = match parse(input) {
+        Ok(data) => data,
+        Err(error) => {
+            println!("ERROR: {error}");
+            return;
+        }
+    };
+    println!("Day {}: {}", day.day, day.title);
+    println!("Type 'help' to list the available commands.");
+    let stdin = io::stdin();
+    loop {
+        print!("day{:02}> ", day.day);
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 {
+            println!();
+            break;
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((&name, args)) = words.split_first() else {
+            continue;
+        };
+        match name {
+            "quit" | "exit" => break,
+            "help" => {
+                if !help.is_empty() {
+                    println!("{help}");
+                }
+                println!("{GENERIC_HELP}");
+            }
+            // the parsers and solvers panic on unexpected states, but that
+            // shouldn't end the session
+            "reload" => match panic::catch_unwind(AssertUnwindSafe(|| parse(input))) {
+                Ok(Ok(new_data)) => data = new_data,
+                Ok(Err(error)) => println!("ERROR: {error}"),
+                Err(_) => println!("ERROR: the input could not be parsed"),
+            },
+            "solve" => match panic::catch_unwind(AssertUnwindSafe(|| (day.solve)(input))) {
+                Ok(Ok(steps)) => {
+                    for step in steps {
+                        let answer = step.answer.unwrap_or_default();
+                        println!("{:<6} {:>10.3?}  {answer}", step.name, step.elapsed);
+                    }
+                }
+                Ok(Err(error)) => println!("ERROR: {error}"),
+                Err(_) => println!("ERROR: the command failed"),
+            },
+            _ => match panic::catch_unwind(AssertUnwindSafe(|| command(&mut data, name, args))) {
+                Ok(Some(Ok(output))) => println!("{output}"),
+                Ok(Some(Err(error))) => println!("ERROR: {error}"),
+                Ok(None) => println!("ERROR: unknown command '{name}', type 'help' for help"),
+                Err(_) => println!("ERROR: the command failed"),
+            },
+        }
+    }
+}
+
+fn no_command(_: &mut (), _: &str, _: &[&str]) -> CommandResult {
+    None
+}
+
+/// Starts an interactive session to explore the given day's puzzle state.
+pub fn repl(day: &Day, input: &str) {
+    match day.day {
+        7 => session(
+            day,
+            input,
+            infallible(day07::parse),
+            day07::REPL_HELP,
+            day07::repl_command,
+        ),
+        12 => session(
+            day,
+            input,
+            infallible(day12::parse),
+            day12::REPL_HELP,
+            day12::repl_command,
+        ),
+        14 => session(
+            day,
+            input,
+            infallible(day14::parse),
+            day14::REPL_HELP,
+            day14::repl_command,
+        ),
+        15 => session(
+            day,
+            input,
+            infallible(day15::parse),
+            day15::REPL_HELP,
+            |sensors, command, args| day15::repl_command(sensors, command, args),
+        ),
+        16 => session(
+            day,
+            input,
+            day16::try_parse,
+            day16::REPL_HELP,
+            day16::repl_command,
+        ),
+        17 => session(
+            day,
+            input,
+            infallible(day17::parse),
+            day17::REPL_HELP,
+            day17::repl_command,
+        ),
+        19 => session(
+            day,
+            input,
+            infallible(day19::parse),
+            day19::REPL_HELP,
+            |blueprints, command, args| day19::repl_command(blueprints, command, args),
+        ),
+        21 => session(
+            day,
+            input,
+            day21::try_parse,
+            day21::REPL_HELP,
+            day21::repl_command,
+        ),
+        22 => session(
+            day,
+            input,
+            infallible(day22::parse),
+            day22::REPL_HELP,
+            day22::repl_command,
+        ),
+        _ => session(day, input, infallible(|_| ()), "", no_command),
+    }
+}
@@ -0,0 +1,167 @@
+use super::registry::{get_day, Step, DAYS};
+use serde_json::{json, Value};
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+pub struct Config {
+    pub address: String,
+    pub max_body_size: usize,
+    pub timeout: Duration,
+    pub max_running_solvers: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1:8080".to_string(),
+            max_body_size: 1 << 20,
+            timeout: Duration::from_secs(10),
+            max_running_solvers: 4,
+        }
+    }
+}
+
+/// Decrements the number of running solvers when the solver thread ends, even
+/// if it panics.
+struct RunningSolver(Arc<AtomicUsize>);
+
+impl Drop for RunningSolver {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn steps_to_json(steps: &[Step]) -> Value {
+    let total_time = steps.iter().map(|step| step.elapsed).sum::<Duration>();
+    let answers = steps
+        .iter()
+        .filter_map(|step| step.answer.as_ref())
+        .collect::<Vec<_>>();
+    let timings = steps
+        .iter()
+        .map(|step| {
+            (
+                step.name.to_string(),
+                json!(step.elapsed.as_micros() as u64),
+            )
+        })
+        .collect::<serde_json::Map<_, _>>();
+    json!({
+        "answers": answers,
+        "time_us": timings,
+        "total_time_us": total_time.as_micros() as u64,
+    })
+}
+
+fn list_days() -> (u16, Value) {
+    let days = DAYS
+        .iter()
+        .map(|day| json!({"day": day.day, "title": day.title}))
+        .collect::<Vec<_>>();
+    (200, json!(days))
+}
+
+/// Runs the solver in its own thread, so we can give up waiting for it after
+/// the timeout. There's no way to kill a thread, so a timed out solver keeps
+/// running in the background: the running_solvers counter makes sure that a few
+/// pathological inputs cannot pile up and eat all the CPUs.
+fn solve_day(
+    day: u32,
+    request: &mut Request,
+    config: &Config,
+    running_solvers: &Arc<AtomicUsize>,
+) -> (u16, Value) {
+    let Some(day) = get_day(day) else {
+        return (404, json!({"error": format!("unknown day {day}")}));
+    };
+    if request.body_length().unwrap_or(0) > config.max_body_size {
+        return (413, json!({"error": "input is too large"}));
+    }
+    let mut input = String::new();
+    let max_length = config.max_body_size as u64 + 1;
+    if request
+        .as_reader()
+        .take(max_length)
+        .read_to_string(&mut input)
+        .is_err()
+    {
+        return (400, json!({"error": "input must be valid UTF-8"}));
+    }
+    if input.len() > config.max_body_size {
+        return (413, json!({"error": "input is too large"}));
+    }
+    if running_solvers.fetch_add(1, Ordering::SeqCst) >= config.max_running_solvers {
+        running_solvers.fetch_sub(1, Ordering::SeqCst);
+        return (
+            503,
+            json!({"error": "too many running solvers, try again later"}),
+        );
+    }
+    let (sender, receiver) = mpsc::channel();
+    let solve = day.solve;
+    let running_solver = RunningSolver(Arc::clone(running_solvers));
+    thread::spawn(move || {
+        let _running_solver = running_solver;
+        let steps = solve(input.trim_end());
+        let _ = sender.send(steps);
+    });
+    match receiver.recv_timeout(config.timeout) {
+        Ok(Ok(steps)) => {
+            let mut result = steps_to_json(&steps);
+            result["day"] = json!(day.day);
+            (200, result)
+        }
+        Ok(Err(error)) => (422, json!({"error": format!("invalid input: {error}")})),
+        Err(mpsc::RecvTimeoutError::Timeout) => (504, json!({"error": "solver timed out"})),
+        Err(mpsc::RecvTimeoutError::Disconnected) => {
+            // the solver panicked, most likely because of an invalid input
+            (
+                422,
+                json!({"error": "solver failed, please check the input"}),
+            )
+        }
+    }
+}
+
+fn handle(mut request: Request, config: &Config, running_solvers: &Arc<AtomicUsize>) {
+    let url = request.url().to_string();
+    let path = url.split('?').next().unwrap_or_default();
+    let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+    let (status, body) = match (request.method(), &segments[..]) {
+        (Method::Get, ["days"]) => list_days(),
+        (Method::Post, ["solve", day]) => match day.parse() {
+            Ok(day) => solve_day(day, &mut request, config, running_solvers),
+            Err(_) => (404, json!({"error": format!("unknown day {day}")})),
+        },
+        (_, ["days"]) | (_, ["solve", _]) => (405, json!({"error": "method not allowed"})),
+        _ => (404, json!({"error": "not found"})),
+    };
+    let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(header);
+    let _ = request.respond(response);
+}
+
+/// Serves the solvers over HTTP:
+/// - `GET /days` lists the available days
+/// - `POST /solve/{day}` solves the given day, using the request body as input
+///
+/// Only returns if the server can't listen on the configured address.
+pub fn serve(config: Config) -> Result<(), String> {
+    let server = Server::http(&config.address)
+        .map_err(|error| format!("cannot listen on {}: {error}", config.address))?;
+    println!("Listening on http://{}", config.address);
+    let config = Arc::new(config);
+    let running_solvers = Arc::new(AtomicUsize::new(0));
+    for request in server.incoming_requests() {
+        let config = Arc::clone(&config);
+        let running_solvers = Arc::clone(&running_solvers);
+        thread::spawn(move || handle(request, &config, &running_solvers));
+    }
+    Ok(())
+}